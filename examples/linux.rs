@@ -0,0 +1,32 @@
+//! Lecture d'un DS1621 branche sur le bus `/dev/i2c-1` d'un Raspberry Pi (ou de toute
+//! carte Linux), broches A2-A0 a la masse :
+//!
+//! ```text
+//! cargo run --example linux --features hal-1 --target x86_64-unknown-linux-gnu
+//! ```
+
+use std::thread;
+use std::time::Duration;
+
+use ds1621::{Address, Ds1621, Hal1};
+use linux_embedded_hal::I2cdev;
+
+fn main() {
+    let i2c = I2cdev::new("/dev/i2c-1").expect("ouverture de /dev/i2c-1");
+    let mut sensor = Ds1621::new(Hal1::new(i2c), Address::PINS_000);
+
+    if let Err(e) = sensor.start_continuous() {
+        eprintln!("DS1621 injoignable : {}", e);
+        return;
+    }
+    //Laisse passer une premiere conversion complete
+    thread::sleep(Duration::from_millis(sensor.max_conversion_time_ms() as u64));
+
+    loop {
+        match sensor.read_temperature() {
+            Ok(t) => println!("{:.1} °C", t),
+            Err(e) => eprintln!("erreur de lecture : {}", e),
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,448 @@
+//! Driver asynchrone
+//!
+//! [`Ds1621Async`] offre les memes operations que [`Ds1621`](crate::Ds1621) au-dessus du
+//! trait `embedded_hal_async::i2c::I2c`, pour les executeurs asynchrones comme Embassy.
+//! Le codage des registres (temperature, configuration) est commun aux deux drivers.
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    config_with_polarity, decode_millicelsius, encode_half_degrees, Address, ConfigRegBits, Error,
+    Mode, Register, Resolution, Status, CONVERSION_MAX_POLLS, NV_BUSY_LOOP_POLLS,
+};
+#[cfg(feature = "float")]
+use crate::{decode_temp, encode_temp};
+
+//Intervalle de scrutation du bit DONE par measure_once
+const CONVERSION_POLL_MS: u32 = 10;
+
+#[derive(Debug)]
+pub struct Ds1621Async<I2C> {
+    i2c: I2C,
+    addr: u8,
+    mode: Mode,
+    max_conversion_polls: u32,
+    nv_pending: bool,
+}
+
+impl<I2C> Ds1621Async<I2C> {
+    pub fn new_default(i2c: I2C) -> Self {
+        Self::new(i2c, Address::default())
+    }
+
+    pub fn new(i2c: I2C, a_addr: Address) -> Self {
+        Ds1621Async {
+            i2c,
+            addr: a_addr.value(),
+            mode: Mode::Continuous, //By default set CONTINUOUS MODE
+            max_conversion_polls: CONVERSION_MAX_POLLS,
+            nv_pending: false,
+        }
+    }
+
+    /// Detruit le driver et rend le bus I2C
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Nombre maximal de lectures du bit DONE avant [`Error::Timeout`] (100 par defaut,
+    /// espacees de 10ms par [`measure_once`](Self::measure_once), qui lit DONE au
+    /// moins deux fois)
+    pub fn set_conversion_timeout(&mut self, max_polls: u32) {
+        self.max_conversion_polls = max_polls;
+    }
+}
+
+impl<I2C, E> Ds1621Async<I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    pub async fn read_config(&mut self) -> Result<u8, Error<E>> {
+        let mut u8rd_buff: [u8; 1] = [0; 1];
+
+        match self
+            .i2c
+            .write_read(self.addr, &[Register::ACCESS_CONFIG], &mut u8rd_buff)
+            .await
+        {
+            Ok(()) => {
+                if u8rd_buff[0] & ConfigRegBits::NVB == 0 {
+                    self.nv_pending = false;
+                }
+                Ok(u8rd_buff[0])
+            }
+            Err(e) => Err(Error::I2C(e)),
+        }
+    }
+
+    /// Ecrit le registre de configuration, apres la fin d'une eventuelle ecriture en
+    /// EEPROM en cours (bit NVB) comme [`Ds1621::write_config`](crate::Ds1621::write_config).
+    /// Renvoie [`Error::Timeout`] si NVB reste leve apres 1000 lectures.
+    pub async fn write_config(&mut self, a_config: u8) -> Result<(), Error<E>> {
+        self.wait_nv_ready().await?;
+        self.write(&[Register::ACCESS_CONFIG, a_config]).await?;
+        self.nv_pending = true;
+        Ok(())
+    }
+
+    //Scrutation de NVB, seulement si une ecriture en EEPROM est peut-etre en cours
+    async fn wait_nv_ready(&mut self) -> Result<(), Error<E>> {
+        if !self.nv_pending {
+            return Ok(());
+        }
+
+        for _ in 0..NV_BUSY_LOOP_POLLS {
+            if self.read_config().await? & ConfigRegBits::NVB == 0 {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    pub async fn set_convert_mode(&mut self, a_mode: Mode) -> Result<(), Error<E>> {
+        let conf_val = self.read_config().await?;
+        self.write_config(a_mode.apply(conf_val)).await?;
+        self.mode = a_mode;
+        Ok(())
+    }
+
+    /// Lit le mode de conversion reellement programme dans le capteur (bit 1SHOT)
+    pub async fn get_convert_mode(&mut self) -> Result<Mode, Error<E>> {
+        Ok(Mode::from_config(self.read_config().await?))
+    }
+
+    /// Choisit le niveau actif de la sortie thermostat Tout (bit POL)
+    pub async fn set_polarity(&mut self, active_high: bool) -> Result<(), Error<E>> {
+        let conf_val = self.read_config().await?;
+        self.write_config(config_with_polarity(conf_val, active_high))
+            .await
+    }
+
+    /// Remet a zero les indicateurs verrouilles THF et TLF
+    pub async fn clear_flags(&mut self) -> Result<(), Error<E>> {
+        let conf_val = self.read_config().await?;
+        self.write_config(conf_val & !ConfigRegBits::FLAGS).await
+    }
+
+    /// Lit le registre de configuration et decode les indicateurs d'etat
+    pub async fn read_status(&mut self) -> Result<Status, Error<E>> {
+        Ok(Status::from_config(self.read_config().await?))
+    }
+
+    pub async fn start_convert(&mut self) -> Result<(), Error<E>> {
+        self.write(&[Register::START_CONVERT]).await
+    }
+
+    pub async fn stop_convert(&mut self) -> Result<(), Error<E>> {
+        self.write(&[Register::STOP_CONVERT]).await
+    }
+
+    #[cfg(feature = "float")]
+    pub async fn read_temperature(&mut self) -> Result<f32, Error<E>> {
+        let raw_read = self.read_register_raw(Register::TEMPERATURE).await?;
+        Ok(decode_temp(raw_read[0], raw_read[1], Resolution::Bits9))
+    }
+
+    /// Lit la temperature en millidegres Celsius, sans virgule flottante
+    pub async fn read_temperature_millicelsius(&mut self) -> Result<i32, Error<E>> {
+        let raw_read = self.read_register_raw(Register::TEMPERATURE).await?;
+        Ok(decode_millicelsius(
+            raw_read[0],
+            raw_read[1],
+            Resolution::Bits9,
+        ))
+    }
+
+    #[cfg(feature = "float")]
+    pub async fn write_high_temperature(&mut self, a_temp: f32) -> Result<f32, Error<E>> {
+        self.write_threshold_temperature(Register::ACCESS_TH, a_temp)
+            .await
+    }
+
+    #[cfg(feature = "float")]
+    pub async fn write_low_temperature(&mut self, a_temp: f32) -> Result<f32, Error<E>> {
+        self.write_threshold_temperature(Register::ACCESS_TL, a_temp)
+            .await
+    }
+
+    /// Ecrit un seuil TH ou TL exprime en demi-degres (51 => 25.5°C)
+    pub async fn write_threshold_half_degrees(
+        &mut self,
+        halves: i16,
+        reg: u8,
+    ) -> Result<(), Error<E>> {
+        if reg != Register::ACCESS_TL && reg != Register::ACCESS_TH {
+            return Err(Error::InvalidRegister);
+        }
+
+        self.write_threshold(reg, encode_half_degrees(halves as i32))
+            .await
+    }
+
+    /// Attend la fin de la conversion en cours en scrutant le bit DONE toutes les 10ms
+    pub async fn wait_for_conversion<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        for poll in 0..self.max_conversion_polls {
+            if poll != 0 {
+                delay.delay_ms(CONVERSION_POLL_MS).await;
+            }
+
+            if self.read_config().await? & ConfigRegBits::DONE != 0 {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    #[cfg(feature = "float")]
+    //Comme Ds1621::wait_for_fresh_conversion : juste apres un Start Convert, DONE peut
+    //encore etre celui de la conversion precedente et n'est retenu qu'a partir de la
+    //deuxieme lecture, deux lectures au moins etant faites
+    async fn wait_for_fresh_conversion<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        for poll in 0..self.max_conversion_polls.max(2) {
+            if poll != 0 {
+                delay.delay_ms(CONVERSION_POLL_MS).await;
+            }
+
+            let conf_val = self.read_config().await?;
+            if poll != 0 && conf_val & ConfigRegBits::DONE != 0 {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Realise une mesure unique en mode one shot puis restaure le mode precedent,
+    /// comme [`Ds1621::measure_once`](crate::Ds1621::measure_once)
+    #[cfg(feature = "float")]
+    pub async fn measure_once<D>(&mut self, delay: &mut D) -> Result<f32, Error<E>>
+    where
+        D: DelayNs,
+    {
+        let was_continuous = self.get_convert_mode().await? == Mode::Continuous;
+
+        if was_continuous {
+            self.set_convert_mode(Mode::OneShot).await?;
+        }
+
+        let result = match self.start_convert().await {
+            Ok(()) => match self.wait_for_fresh_conversion(delay).await {
+                Ok(()) => self.read_temperature().await,
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+
+        if was_continuous {
+            self.set_convert_mode(Mode::Continuous).await?;
+        }
+
+        result
+    }
+
+    //Seuil arrondi au demi-degre le plus proche, renvoie la valeur programmee
+    #[cfg(feature = "float")]
+    async fn write_threshold_temperature(&mut self, reg: u8, a_temp: f32) -> Result<f32, Error<E>> {
+        match encode_temp(a_temp) {
+            Some(bytes) => {
+                self.write(&[reg, bytes[0], bytes[1]]).await?;
+                self.nv_pending = true;
+                Ok(decode_temp(bytes[0], bytes[1], Resolution::Bits9))
+            }
+            None => Err(Error::OutOfRange),
+        }
+    }
+
+    async fn write_threshold(&mut self, reg: u8, bytes: Option<[u8; 2]>) -> Result<(), Error<E>> {
+        match bytes {
+            Some(bytes) => {
+                self.write(&[reg, bytes[0], bytes[1]]).await?;
+                self.nv_pending = true;
+                Ok(())
+            }
+            None => Err(Error::OutOfRange),
+        }
+    }
+
+    async fn read_register_raw(&mut self, reg: u8) -> Result<[u8; 2], Error<E>> {
+        let mut raw_read: [u8; 2] = [0; 2];
+
+        match self.i2c.write_read(self.addr, &[reg], &mut raw_read).await {
+            Ok(()) => Ok(raw_read),
+            Err(e) => Err(Error::I2C(e)),
+        }
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), Error<E>> {
+        match self.i2c.write(self.addr, bytes).await {
+            Ok(()) => Ok(()),
+            Err(e) => Err(Error::I2C(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+    use embedded_hal_async::i2c::{ErrorType, Operation};
+    use std::vec::Vec;
+
+    //Les futures du mock se terminent immediatement : une seule scrutation suffit
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future not ready"),
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct I2cMock {
+        writes: Vec<Vec<u8>>,
+        reads: Vec<Vec<u8>>,
+    }
+
+    impl I2cMock {
+        fn with_reads(reads: &[&[u8]]) -> Self {
+            I2cMock {
+                writes: Vec::new(),
+                reads: reads.iter().map(|r| r.to_vec()).collect(),
+            }
+        }
+    }
+
+    impl ErrorType for I2cMock {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c for I2cMock {
+        async fn transaction(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                match op {
+                    Operation::Write(bytes) => self.writes.push(bytes.to_vec()),
+                    Operation::Read(buffer) => {
+                        buffer.copy_from_slice(&self.reads.remove(0));
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[derive(Debug, Default)]
+    struct DelayMock {
+        elapsed_ms: u32,
+    }
+
+    #[cfg(feature = "float")]
+    impl DelayNs for DelayMock {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.elapsed_ms += ns / 1_000_000;
+        }
+    }
+
+    #[test]
+    fn threshold_encoding_matches_blocking_driver() {
+        let mut sensor = Ds1621Async::new_default(I2cMock::with_reads(&[]));
+
+        assert!(block_on(sensor.write_threshold_half_degrees(-11, Register::ACCESS_TL)).is_ok());
+        assert!(matches!(
+            block_on(sensor.write_threshold_half_degrees(51, Register::TEMPERATURE)),
+            Err(Error::InvalidRegister)
+        ));
+        assert_eq!(sensor.i2c.writes, [[Register::ACCESS_TL, 0xFA, 0x80]]);
+    }
+
+    #[test]
+    fn config_bits_match_blocking_driver() {
+        let mut sensor =
+            Ds1621Async::new_default(I2cMock::with_reads(&[&[0b0110_0001], &[0b0000_0001]]));
+
+        assert!(block_on(sensor.clear_flags()).is_ok());
+        assert!(block_on(sensor.set_polarity(true)).is_ok());
+        assert_eq!(sensor.i2c.writes[1], [Register::ACCESS_CONFIG, 0b0000_0001]);
+        assert_eq!(sensor.i2c.writes[3], [Register::ACCESS_CONFIG, 0b0000_0011]);
+    }
+
+    #[test]
+    fn config_write_waits_for_nv_write() {
+        //NVB leve a la lecture de set_polarity, puis a la premiere scrutation
+        let mut sensor =
+            Ds1621Async::new_default(I2cMock::with_reads(&[&[0x10], &[0x10], &[0x00]]));
+
+        assert!(block_on(sensor.write_threshold_half_degrees(60, Register::ACCESS_TH)).is_ok());
+        assert!(block_on(sensor.set_polarity(true)).is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_TH, 0x1E, 0x00],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0x12],
+            ]
+        );
+
+        let busy: Vec<&[u8]> = std::vec![&[0x10]; 1000];
+        let mut sensor = Ds1621Async::new_default(I2cMock::with_reads(&busy));
+        assert!(block_on(sensor.write_threshold_half_degrees(50, Register::ACCESS_TL)).is_ok());
+        assert!(matches!(
+            block_on(sensor.write_config(0x00)),
+            Err(Error::Timeout)
+        ));
+        assert_eq!(sensor.i2c.writes.len(), 1001);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn measure_once_async() {
+        let mut sensor = Ds1621Async::new_default(I2cMock::with_reads(&[
+            &[0x00],
+            &[0x00],
+            //DONE encore leve par la conversion precedente
+            &[0x81],
+            &[0x81],
+            &[0xF5, 0x80],
+            &[0x81],
+        ]));
+        let mut delay = DelayMock::default();
+
+        assert_eq!(block_on(sensor.measure_once(&mut delay)).unwrap(), -10.5);
+        assert_eq!(delay.elapsed_ms, 10);
+        assert_eq!(sensor.i2c.writes[2], [Register::ACCESS_CONFIG, 0x01]);
+        assert_eq!(sensor.i2c.writes[3], [Register::START_CONVERT]);
+        assert_eq!(sensor.i2c.writes[8], [Register::ACCESS_CONFIG, 0x80]);
+
+        //Une seule lecture autorisee : la lecture perimee n'epuise pas la limite
+        let mut sensor = Ds1621Async::new_default(I2cMock::with_reads(&[
+            &[0x01],
+            &[0x81],
+            &[0x81],
+            &[0x19, 0x00],
+        ]));
+        sensor.set_conversion_timeout(1);
+        assert_eq!(block_on(sensor.measure_once(&mut delay)).unwrap(), 25.0);
+    }
+}
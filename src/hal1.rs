@@ -0,0 +1,66 @@
+//! Adaptateur embedded-hal 1.0
+//!
+//! Le driver est ecrit pour les traits `blocking::i2c` d'embedded-hal 0.2. [`Hal1`]
+//! enveloppe un bus implementant le trait unifie `embedded_hal::i2c::I2c` de la
+//! version 1.0 pour qu'il puisse etre utilise tel quel avec [`Ds1621`](crate::Ds1621) :
+//!
+//! ```ignore
+//! let sensor = Ds1621::new(Hal1::new(i2c), Address::PINS_000);
+//! ```
+//!
+//! Le driver possede son bus : pour placer plusieurs capteurs sur le meme bus, chacun
+//! recoit un `I2cDevice` d'`embedded-hal-bus` (`RefCellDevice`, `CriticalSectionDevice`...)
+//! qui partage le bus sous-jacent :
+//!
+//! ```ignore
+//! let bus = RefCell::new(i2c);
+//! let inside = Ds1621::new(Hal1::new(RefCellDevice::new(&bus)), Address::PINS_000);
+//! let outside = Ds1621::new(Hal1::new(RefCellDevice::new(&bus)), Address::PINS_001);
+//! ```
+
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal_1::i2c::I2c;
+
+/// Bus I2C embedded-hal 1.0 expose au travers des traits embedded-hal 0.2
+#[derive(Debug)]
+pub struct Hal1<I2C>(I2C);
+
+impl<I2C> Hal1<I2C> {
+    pub fn new(i2c: I2C) -> Self {
+        Hal1(i2c)
+    }
+
+    /// Rend le bus I2C d'origine
+    pub fn into_inner(self) -> I2C {
+        self.0
+    }
+}
+
+impl<I2C: I2c> Read for Hal1<I2C> {
+    type Error = I2C::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        I2c::read(&mut self.0, address, buffer)
+    }
+}
+
+impl<I2C: I2c> Write for Hal1<I2C> {
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        I2c::write(&mut self.0, address, bytes)
+    }
+}
+
+impl<I2C: I2c> WriteRead for Hal1<I2C> {
+    type Error = I2C::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        I2c::write_read(&mut self.0, address, bytes, buffer)
+    }
+}
@@ -1,24 +1,332 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(feature = "no_std", no_std)]
 
-use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use core::convert::Infallible;
+#[cfg(feature = "float")]
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
 
+#[cfg(feature = "hal-1")]
+mod hal1;
+#[cfg(feature = "hal-1")]
+pub use hal1::Hal1;
+
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "async")]
+pub use asynch::Ds1621Async;
+
+mod unified;
+pub use unified::Unified;
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<E> {
     I2C(E),
-    INVALID_PARAMETER,
+    InvalidParameter,
+    /// Le capteur n'a pas termine a temps la conversion ou l'ecriture en EEPROM
+    /// attendue (bit DONE ou NVB)
+    Timeout,
+    /// La valeur relue ne correspond pas a celle ecrite
+    Verify,
+    /// Le registre indique n'est pas un seuil TH/TL (erreur de programmation)
+    InvalidRegister,
+    /// Temperature hors de la plage representable ou de fonctionnement
+    OutOfRange,
+    /// Le `core::fmt::Write` fourni a [`Ds1621::write_report`] a refuse l'ecriture
+    Format,
 }
 
-#[derive(Debug, Copy, Clone, Default)]
-struct Config {
-    bits: u8,
+impl<E> Error<E> {
+    #[deprecated(note = "renamed to Error::InvalidParameter")]
+    pub const INVALID_PARAMETER: Self = Error::InvalidParameter;
+
+    /// Convertit l'erreur de bus, par exemple vers l'erreur de l'application
+    pub fn map_i2c<F>(self, f: impl FnOnce(E) -> F) -> Error<F> {
+        match self {
+            Error::I2C(e) => Error::I2C(f(e)),
+            Error::InvalidParameter => Error::InvalidParameter,
+            Error::Timeout => Error::Timeout,
+            Error::Verify => Error::Verify,
+            Error::InvalidRegister => Error::InvalidRegister,
+            Error::OutOfRange => Error::OutOfRange,
+            Error::Format => Error::Format,
+        }
+    }
 }
 
-#[derive(Debug)]
-pub enum MODE {
-    CONTINOUS,
-    ONE_SHOT,
+impl<E> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::I2C(_) => f.write_str("I2C bus error"),
+            Error::InvalidParameter => f.write_str("invalid parameter"),
+            Error::Timeout => f.write_str("timeout waiting for the sensor"),
+            Error::Verify => f.write_str("readback mismatch"),
+            Error::InvalidRegister => f.write_str("invalid register"),
+            Error::OutOfRange => f.write_str("value out of range"),
+            Error::Format => f.write_str("formatting error"),
+        }
+    }
+}
+
+/// Contenu du registre de configuration, un champ par bit
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Config {
+    /// DONE : conversion terminee (lecture seule)
+    pub conversion_done: bool,
+    /// THF : seuil haut depasse (verrouille)
+    pub temp_high_flag: bool,
+    /// TLF : seuil bas franchi (verrouille)
+    pub temp_low_flag: bool,
+    /// NVB : ecriture EEPROM en cours (lecture seule)
+    pub nv_busy: bool,
+    /// POL : sortie Tout active a l'etat haut
+    pub active_high: bool,
+    /// 1SHOT : mode one shot
+    pub one_shot: bool,
+}
+
+impl Config {
+    /// Decode l'octet lu par [`Ds1621::read_config`]
+    pub fn from_bits(bits: u8) -> Self {
+        Config {
+            conversion_done: bits & ConfigRegBits::DONE != 0,
+            temp_high_flag: bits & ConfigRegBits::THF != 0,
+            temp_low_flag: bits & ConfigRegBits::TLF != 0,
+            nv_busy: bits & ConfigRegBits::NVB != 0,
+            active_high: bits & ConfigRegBits::POL != 0,
+            one_shot: bits & ConfigRegBits::ONE_SHOT != 0,
+        }
+    }
+
+    /// Octet a ecrire avec [`Ds1621::write_config`], les bits reserves sont a zero
+    pub fn to_bits(&self) -> u8 {
+        let mut bits = 0;
+        for (set, bit) in [
+            (self.conversion_done, ConfigRegBits::DONE),
+            (self.temp_high_flag, ConfigRegBits::THF),
+            (self.temp_low_flag, ConfigRegBits::TLF),
+            (self.nv_busy, ConfigRegBits::NVB),
+            (self.active_high, ConfigRegBits::POL),
+            (self.one_shot, ConfigRegBits::ONE_SHOT),
+        ] {
+            if set {
+                bits |= bit;
+            }
+        }
+
+        bits
+    }
+
+    /// Mode de conversion correspondant au bit 1SHOT
+    pub fn mode(&self) -> Mode {
+        if self.one_shot {
+            Mode::OneShot
+        } else {
+            Mode::Continuous
+        }
+    }
+}
+
+/// Configuration complete d'un capteur, appliquee par [`Ds1621::apply_config`] :
+/// utile pour programmer une serie de cartes a l'identique
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FullConfig {
+    pub mode: Mode,
+    /// Sortie Tout active a l'etat haut
+    pub active_high: bool,
+    /// Seuil haut TH
+    pub high: Temperature,
+    /// Seuil bas TL
+    pub low: Temperature,
+}
+
+/// Etat des indicateurs du registre de configuration
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Status {
+    /// Conversion de temperature terminee (DONE)
+    pub conversion_done: bool,
+    /// La temperature a depasse le seuil haut TH (THF, verrouille)
+    pub temp_high_flag: bool,
+    /// La temperature est descendue sous le seuil bas TL (TLF, verrouille)
+    pub temp_low_flag: bool,
+    /// Ecriture en memoire non volatile en cours (NVB)
+    pub nv_busy: bool,
+}
+
+impl Status {
+    fn from_config(bits: u8) -> Self {
+        Status {
+            conversion_done: bits & ConfigRegBits::DONE != 0,
+            temp_high_flag: bits & ConfigRegBits::THF != 0,
+            temp_low_flag: bits & ConfigRegBits::TLF != 0,
+            nv_busy: bits & ConfigRegBits::NVB != 0,
+        }
+    }
+}
+
+/// Etat complet du capteur renvoye par [`Ds1621::dump`], pour un diagnostic
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceState {
+    /// Mode de conversion programme (bit 1SHOT)
+    pub mode: Mode,
+    /// Sortie Tout active a l'etat haut (bit POL)
+    pub active_high: bool,
+    /// Indicateur THF
+    pub temp_high_flag: bool,
+    /// Indicateur TLF
+    pub temp_low_flag: bool,
+    /// Ecriture en EEPROM en cours (NVB)
+    pub nv_busy: bool,
+    /// Seuil haut TH
+    pub high: Temperature,
+    /// Seuil bas TL
+    pub low: Temperature,
+}
+
+/// Temperature en demi-degres Celsius, telle que renvoyee par
+/// [`Ds1621::read_temperature_raw`], affichable sans virgule flottante :
+///
+/// ```ignore
+/// let t = Temperature::from_half_degrees(sensor.read_temperature_raw()?);
+/// write!(lcd, "{}", t)?;   // "25.5"
+/// write!(lcd, "{:#}", t)?; // "25.5°C"
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Temperature(i16);
+
+impl Temperature {
+    pub fn from_half_degrees(halves: i16) -> Self {
+        Temperature(halves)
+    }
+
+    /// Valeur en demi-degres (25.5°C => 51)
+    pub fn half_degrees(&self) -> i16 {
+        self.0
+    }
+
+    #[cfg(feature = "float")]
+    /// Valeur en degres Celsius
+    pub fn celsius(&self) -> f32 {
+        self.0 as f32 / 2.0
+    }
+}
+
+impl core::fmt::Display for Temperature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        //Signe traite a part pour afficher -0.5 et non 0.5
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let halves = self.0.unsigned_abs();
+        let decimal = if halves & 1 != 0 { 5 } else { 0 };
+        write!(f, "{}{}.{}", sign, halves >> 1, decimal)?;
+
+        //Le format alternatif {:#} ajoute l'unite
+        if f.alternate() {
+            f.write_str("°C")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "float")]
+impl From<Temperature> for f32 {
+    fn from(t: Temperature) -> f32 {
+        t.celsius()
+    }
+}
+
+/// Position d'une temperature par rapport aux seuils du thermostat
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ThermostatState {
+    /// Au niveau ou au-dessus de TH (THF serait leve)
+    Above,
+    /// Strictement entre TL et TH
+    Within,
+    /// Au niveau ou en dessous de TL (TLF serait leve)
+    Below,
+}
+
+/// Arrondi d'un seuil sur la grille de 0.5°C des registres TH/TL, voir
+/// [`Ds1621::write_threshold_rounded`]
+#[cfg(feature = "float")]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RoundMode {
+    /// Au demi-degre le plus proche, egalite vers le haut (25.3 => 25.5, 25.2 => 25.0)
+    #[default]
+    Nearest,
+    /// Vers le bas (25.7 => 25.5)
+    Down,
+    /// Vers le haut (25.3 => 25.5)
+    Up,
+}
+
+/// Avancement d'une conversion renvoye par [`Ds1621::poll_conversion`]
+#[cfg(feature = "float")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Conversion {
+    /// Bit DONE encore a zero
+    InProgress,
+    /// Conversion terminee, temperature en degres Celsius
+    Done(f32),
+}
+
+/// Franchissement de seuil renvoye par [`Ds1621::check_crossings`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Crossing {
+    /// THF etait leve : la temperature a atteint TH
+    High,
+    /// TLF etait leve : la temperature est descendue sous TL
+    Low,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    Continuous,
+    OneShot,
+}
+
+impl Mode {
+    /// Mode code par le bit 1SHOT d'un octet de configuration (1 = one shot),
+    /// les autres bits etant ignores
+    pub fn from_config(bits: u8) -> Mode {
+        if bits & ConfigRegBits::ONE_SHOT != 0 {
+            Mode::OneShot
+        } else {
+            Mode::Continuous
+        }
+    }
+
+    /// Octet de configuration `bits` avec le bit 1SHOT positionne pour ce mode, les
+    /// autres bits etant conserves
+    pub fn apply(self, bits: u8) -> u8 {
+        match self {
+            Mode::Continuous => bits & !ConfigRegBits::ONE_SHOT,
+            Mode::OneShot => bits | ConfigRegBits::ONE_SHOT,
+        }
+    }
+
+    #[deprecated(note = "renamed to Mode::Continuous")]
+    pub const CONTINOUS: Mode = Mode::Continuous;
+    #[deprecated(note = "renamed to Mode::OneShot")]
+    pub const ONE_SHOT: Mode = Mode::OneShot;
 }
 
+#[deprecated(note = "renamed to Mode")]
+#[allow(clippy::upper_case_acronyms)]
+pub type MODE = Mode;
+
 struct Register;
 impl Register {
     const TEMPERATURE: u8 = 0xAA;
@@ -26,6 +334,7 @@ impl Register {
     const ACCESS_TL: u8 = 0xA2;
     const ACCESS_CONFIG: u8 = 0xAC;
     const START_CONVERT: u8 = 0xEE;
+    const START_CONVERT_DS1631: u8 = 0x51;
     const STOP_CONVERT: u8 = 0x22;
     const READ_COUNTER: u8 = 0xA8;
     const READ_SLOPE: u8 = 0xA9;
@@ -42,230 +351,4073 @@ impl ConfigRegBits {
     const RESERVED1: u8 = 0b0000_0100;
     const POL: u8 = 0b0000_0010;
     const ONE_SHOT: u8 = 0b0000_0001;
+    //Sur les DS1631/DS1721 les bits reserves selectionnent la resolution
+    const R1: u8 = Self::RESERVED0;
+    const R0: u8 = Self::RESERVED1;
+    //Indicateurs de thermostat verrouilles
+    const FLAGS: u8 = Self::THF | Self::TLF;
+    //Bits conserves tels qu'ecrits
+    const WRITABLE: u8 = Self::POL | Self::ONE_SHOT;
+}
+
+/// Masques des bits du registre de configuration, pour interpreter l'octet
+/// renvoye par [`Ds1621::read_config`] ou composer celui de [`Ds1621::write_config`]
+pub mod raw {
+    use super::ConfigRegBits;
+
+    /// Conversion terminee (lecture seule)
+    pub const DONE: u8 = ConfigRegBits::DONE;
+    /// Seuil haut depasse (verrouille jusqu'a l'ecriture d'un 0)
+    pub const THF: u8 = ConfigRegBits::THF;
+    /// Seuil bas franchi (verrouille jusqu'a l'ecriture d'un 0)
+    pub const TLF: u8 = ConfigRegBits::TLF;
+    /// Ecriture en EEPROM en cours (lecture seule)
+    pub const NVB: u8 = ConfigRegBits::NVB;
+    /// Resolution, bit de poids fort (DS1631/DS1721)
+    pub const R1: u8 = ConfigRegBits::R1;
+    /// Resolution, bit de poids faible (DS1631/DS1721)
+    pub const R0: u8 = ConfigRegBits::R0;
+    /// Sortie Tout active a l'etat haut
+    pub const POL: u8 = ConfigRegBits::POL;
+    /// Mode one shot
+    pub const ONE_SHOT: u8 = ConfigRegBits::ONE_SHOT;
+}
+
+/// Octets de commande du DS1621, pour des transactions personnalisees sur le bus rendu
+/// par [`Ds1621::release`] ou pour etendre le driver
+pub mod registers {
+    use super::Register;
+
+    /// Lecture de la temperature (2 octets)
+    pub const TEMPERATURE: u8 = Register::TEMPERATURE;
+    /// Lecture/ecriture du seuil haut TH (2 octets)
+    pub const ACCESS_TH: u8 = Register::ACCESS_TH;
+    /// Lecture/ecriture du seuil bas TL (2 octets)
+    pub const ACCESS_TL: u8 = Register::ACCESS_TL;
+    /// Lecture/ecriture du registre de configuration (1 octet)
+    pub const ACCESS_CONFIG: u8 = Register::ACCESS_CONFIG;
+    /// Debut de conversion (DS1621)
+    pub const START_CONVERT: u8 = Register::START_CONVERT;
+    /// Debut de conversion (DS1631/DS1721)
+    pub const START_CONVERT_DS1631: u8 = Register::START_CONVERT_DS1631;
+    /// Arret des conversions continues
+    pub const STOP_CONVERT: u8 = Register::STOP_CONVERT;
+    /// Lecture de COUNT_REMAIN (1 octet, DS1621 uniquement)
+    pub const READ_COUNTER: u8 = Register::READ_COUNTER;
+    /// Lecture de COUNT_PER_C (1 octet, DS1621 uniquement)
+    pub const READ_SLOPE: u8 = Register::READ_SLOPE;
+}
+
+/// Resolution de conversion. Le DS1621 est limite a 9 bits (0.5°C), les DS1631/DS1721
+/// vont jusqu'a 12 bits (0.0625°C).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Resolution {
+    #[default]
+    Bits9,
+    Bits10,
+    Bits11,
+    Bits12,
+}
+
+impl Resolution {
+    //Nombre de bits significatifs dans le LSB des registres de temperature
+    fn fraction_bits(self) -> u8 {
+        match self {
+            Resolution::Bits9 => 1,
+            Resolution::Bits10 => 2,
+            Resolution::Bits11 => 3,
+            Resolution::Bits12 => 4,
+        }
+    }
+
+    //Bits R1:R0 du registre de configuration (DS1631)
+    fn config_bits(self) -> u8 {
+        match self {
+            Resolution::Bits9 => 0,
+            Resolution::Bits10 => ConfigRegBits::R0,
+            Resolution::Bits11 => ConfigRegBits::R1,
+            Resolution::Bits12 => ConfigRegBits::R1 | ConfigRegBits::R0,
+        }
+    }
+}
+
+/// Composant pilote. Les DS1631, DS1631A, DS1731 et DS1721 reprennent le brochage
+/// et les commandes du DS1621, a quelques differences pres : commande Start Convert
+/// 0x51, resolution de 9 a 12 bits selectionnee par les bits R1/R0 de la configuration
+/// et absence des registres de compteurs (0xA8/0xA9).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Variant {
+    #[default]
+    Ds1621,
+    Ds1631,
+}
+
+impl Variant {
+    fn start_convert_command(self) -> u8 {
+        match self {
+            Variant::Ds1621 => Register::START_CONVERT,
+            Variant::Ds1631 => Register::START_CONVERT_DS1631,
+        }
+    }
+
+    fn writable_bits(self) -> u8 {
+        match self {
+            Variant::Ds1621 => ConfigRegBits::WRITABLE,
+            Variant::Ds1631 => ConfigRegBits::WRITABLE | ConfigRegBits::R1 | ConfigRegBits::R0,
+        }
+    }
+}
+
+fn config_with_polarity(bits: u8, active_high: bool) -> u8 {
+    if active_high {
+        bits | ConfigRegBits::POL
+    } else {
+        bits & !ConfigRegBits::POL
+    }
+}
+
+//Decodage d'un registre de temperature : le MSB est un entier signe en
+//complement a deux, les bits de poids fort du LSB portent la partie decimale
+//(seul le bit 7, un demi-degre, en 9 bits). Les bits suivants sont ignores.
+fn decode_raw(msb: u8, lsb: u8, resolution: Resolution) -> i16 {
+    i16::from_be_bytes([msb, lsb]) >> (8 - resolution.fraction_bits())
+}
+
+#[cfg(feature = "float")]
+fn decode_temp(msb: u8, lsb: u8, resolution: Resolution) -> f32 {
+    decode_raw(msb, lsb, resolution) as f32 / (1 << resolution.fraction_bits()) as f32
+}
+
+fn decode_millicelsius(msb: u8, lsb: u8, resolution: Resolution) -> i32 {
+    (decode_raw(msb, lsb, resolution) as i32 * 1000) >> resolution.fraction_bits()
+}
+
+//Plancher calcule a la main, f32::floor n'etant pas disponible en no_std
+#[cfg(feature = "float")]
+fn floor_i32(value: f32) -> i32 {
+    let mut floor = value as i32;
+    if value < floor as f32 {
+        floor -= 1;
+    }
+
+    floor
+}
+
+//Conversion en demi-degres selon `round` (au plus proche : 25.3 => 25.5,
+//25.2 => 25.0, egalite vers le haut)
+#[cfg(feature = "float")]
+fn half_degrees_rounded(temp: f32, round: RoundMode) -> i32 {
+    match round {
+        RoundMode::Nearest => floor_i32(temp * 2.0 + 0.5),
+        RoundMode::Down => floor_i32(temp * 2.0),
+        RoundMode::Up => -floor_i32(-temp * 2.0),
+    }
+}
+
+#[cfg(feature = "float")]
+fn half_degrees(temp: f32) -> i32 {
+    half_degrees_rounded(temp, RoundMode::Nearest)
+}
+
+//Plage de fonctionnement du capteur, imposee a tous les seuils programmes :
+//les bornes des autres unites en sont toutes deduites
+const TEMP_MIN_DEGREES: i16 = -55;
+const TEMP_MAX_DEGREES: i16 = 125;
+const TEMP_MIN: f32 = TEMP_MIN_DEGREES as f32;
+const TEMP_MAX: f32 = TEMP_MAX_DEGREES as f32;
+const TEMP_MIN_HALVES: i16 = TEMP_MIN_DEGREES * 2;
+const TEMP_MAX_HALVES: i16 = TEMP_MAX_DEGREES * 2;
+const TEMP_MIN_MC: i32 = TEMP_MIN_DEGREES as i32 * 1000;
+const TEMP_MAX_MC: i32 = TEMP_MAX_DEGREES as i32 * 1000;
+
+//Taille du tampon de read_temperature_stable
+const MAX_STABLE_SAMPLES: u8 = 15;
+
+//Encodage d'une temperature au format des registres TH/TL, `None` si elle
+//n'est pas dans la plage de fonctionnement -55..=125°C (ou n'est pas finie)
+#[cfg(feature = "float")]
+fn encode_temp_rounded(temp: f32, round: RoundMode) -> Option<[u8; 2]> {
+    if !(TEMP_MIN..=TEMP_MAX).contains(&temp) {
+        return None;
+    }
+
+    encode_half_degrees(half_degrees_rounded(temp, round))
+}
+
+#[cfg(feature = "float")]
+fn encode_temp(temp: f32) -> Option<[u8; 2]> {
+    encode_temp_rounded(temp, RoundMode::Nearest)
+}
+
+//Encodage d'un seuil TH/TL, `None` hors de la plage de fonctionnement.
+//Le decalage arithmetique arrondit vers -inf (-11 => -6 + 0.5)
+fn encode_half_degrees(halves: i32) -> Option<[u8; 2]> {
+    if !(TEMP_MIN_HALVES as i32..=TEMP_MAX_HALVES as i32).contains(&halves) {
+        return None;
+    }
+
+    let lsb = if halves & 1 != 0 { 0x80 } else { 0x00 };
+    Some([(halves >> 1) as i8 as u8, lsb])
+}
+
+//Millidegres vers demi-degres au plus proche, egalite vers le haut comme
+//RoundMode::Nearest (25_250 => 51, -250 => 0)
+fn half_degrees_from_mc(mc: i32) -> i32 {
+    (mc + 250).div_euclid(500)
 }
 
+//Mise en forme du rapport de Ds1621::write_report
+fn write_report_lines<W: core::fmt::Write>(
+    w: &mut W,
+    state: &DeviceState,
+    temp: Temperature,
+) -> core::fmt::Result {
+    let mode = match state.mode {
+        Mode::Continuous => "continuous",
+        Mode::OneShot => "one shot",
+    };
+    let polarity = if state.active_high { "high" } else { "low" };
+
+    writeln!(w, "mode: {}", mode)?;
+    writeln!(w, "Tout: active {}", polarity)?;
+    writeln!(w, "TH: {:#}", state.high)?;
+    writeln!(w, "TL: {:#}", state.low)?;
+    writeln!(
+        w,
+        "THF: {} TLF: {} NVB: {}",
+        state.temp_high_flag as u8, state.temp_low_flag as u8, state.nv_busy as u8
+    )?;
+    writeln!(w, "temperature: {:#}", temp)
+}
+
+/// Driver DS1621.
+///
+/// Les methodes sont regroupees selon les traits I2C qu'elles exigent. Le trait
+/// `Read` n'est jamais requis : chaque lecture ecrit d'abord l'octet de commande
+/// dans la meme transaction `WriteRead`.
+///
+/// | Traits I2C              | Methodes                                                    |
+/// |-------------------------|-------------------------------------------------------------|
+/// | aucun                   | constructeurs sans acces au bus ([`new`](Self::new), [`try_new`](Self::try_new), [`new_default`](Self::new_default), [`new_ds1631`](Self::new_ds1631), [`from_pins`](Self::from_pins)), [`release`](Self::release), [`inner_mut`](Self::inner_mut), [`variant`](Self::variant), [`max_conversion_time_ms`](Self::max_conversion_time_ms), reglages du driver |
+/// | `Write`                 | [`start_convert`](Self::start_convert), [`stop_convert`](Self::stop_convert), [`shutdown`](Self::shutdown), [`wake`](Self::wake), ecriture des seuils (`write_*_temperature`, `write_threshold_*`, `write_th_raw`...) |
+/// | `WriteRead`             | [`new_read`](Self::new_read), lectures de la temperature, des seuils, des compteurs et de la configuration, [`wait_for_conversion`](Self::wait_for_conversion), [`wait_for_nv_write`](Self::wait_for_nv_write), [`poll_conversion`](Self::poll_conversion), [`ping`](Self::ping), [`probe`](Self::probe), [`dump`](Self::dump) |
+/// | `Write + WriteRead`     | [`new_with_config`](Self::new_with_config), [`write_config`](Self::write_config) (qui attend la fin d'une ecriture EEPROM en cours), operations lecture-modification-ecriture (mode, polarite, resolution, indicateurs), [`apply_config`](Self::apply_config), [`configure_thermostat`](Self::configure_thermostat) et [`write_thresholds_raw`](Self::write_thresholds_raw) (qui attendent la fin de l'ecriture de TH avant celle de TL), mesures completes ([`measure_once`](Self::measure_once), [`read_temperature_one_shot`](Self::read_temperature_one_shot)...), `write_*_temperature_wait` |
+///
+/// Tous ces traits doivent avoir le meme type `Error`, renvoye dans [`Error::I2C`].
+/// Pour une HAL dont les erreurs different d'un trait a l'autre, envelopper le bus
+/// dans [`Unified`].
 #[derive(Debug)]
-pub struct ds1621<I2C> {
+pub struct Ds1621<I2C> {
     i2c: I2C,
     addr: u8,
-    mode: MODE,
+    mode: Mode,
+    max_conversion_polls: u32,
+    variant: Variant,
+    resolution: Resolution,
+    cached_config: Option<u8>,
+    nv_pending: bool,
+    needs_settle: bool,
+    verify_thresholds: bool,
+    retries: u8,
+    consecutive_errors: u32,
+    nv_poll_delay: Option<fn()>,
+}
+
+/// Copie du driver sur un bus partage clonable : adresse, mode, composant et reglages
+/// sont repris, mais pas la configuration en cache, que l'autre copie peut modifier.
+impl<I2C: Clone> Clone for Ds1621<I2C> {
+    fn clone(&self) -> Self {
+        Ds1621 {
+            i2c: self.i2c.clone(),
+            addr: self.addr,
+            mode: self.mode,
+            max_conversion_polls: self.max_conversion_polls,
+            variant: self.variant,
+            resolution: self.resolution,
+            cached_config: None,
+            nv_pending: self.nv_pending,
+            needs_settle: self.needs_settle,
+            verify_thresholds: self.verify_thresholds,
+            retries: self.retries,
+            consecutive_errors: self.consecutive_errors,
+            nv_poll_delay: self.nv_poll_delay,
+        }
+    }
 }
 
-const ADDR_DEFAULT: u8 = 0x4A;
+#[deprecated(note = "renamed to Ds1621")]
+#[allow(non_camel_case_types)]
+pub type ds1621<I2C> = Ds1621<I2C>;
+
+/// Adresses I2C selon le cablage des broches A2 A1 A0 (0 = masse, 1 = niveau haut)
+pub const ADDR_PINS_000: u8 = 0x48;
+pub const ADDR_PINS_001: u8 = 0x49;
+pub const ADDR_PINS_010: u8 = 0x4A;
+pub const ADDR_PINS_011: u8 = 0x4B;
+pub const ADDR_PINS_100: u8 = 0x4C;
+pub const ADDR_PINS_101: u8 = 0x4D;
+pub const ADDR_PINS_110: u8 = 0x4E;
+pub const ADDR_PINS_111: u8 = 0x4F;
+
+/// Adresse I2C valide d'un DS1621, 0x48..=0x4F : une adresse hors de cette plage
+/// ne peut pas etre construite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Address(u8);
+
+impl Address {
+    pub const PINS_000: Address = Address(ADDR_PINS_000);
+    pub const PINS_001: Address = Address(ADDR_PINS_001);
+    pub const PINS_010: Address = Address(ADDR_PINS_010);
+    pub const PINS_011: Address = Address(ADDR_PINS_011);
+    pub const PINS_100: Address = Address(ADDR_PINS_100);
+    pub const PINS_101: Address = Address(ADDR_PINS_101);
+    pub const PINS_110: Address = Address(ADDR_PINS_110);
+    pub const PINS_111: Address = Address(ADDR_PINS_111);
+
+    /// Adresse fixee par le niveau des broches A2, A1 et A0 (`true` = niveau haut)
+    pub const fn from_pins(a2: bool, a1: bool, a0: bool) -> Self {
+        Address(ADDR_DEFAULT | (a2 as u8) << 2 | (a1 as u8) << 1 | a0 as u8)
+    }
+
+    /// `None` si `addr` est hors de la plage 0x48..=0x4F
+    pub const fn from_u8(addr: u8) -> Option<Self> {
+        if addr >= ADDR_DEFAULT && addr <= ADDR_MAX {
+            Some(Address(addr))
+        } else {
+            None
+        }
+    }
+
+    /// Adresse 7 bits
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for Address {
+    fn default() -> Self {
+        Address::PINS_000
+    }
+}
+
+impl From<Address> for u8 {
+    fn from(addr: Address) -> u8 {
+        addr.0
+    }
+}
+
+//Adresse avec A2, A1 et A0 a la masse
+const ADDR_DEFAULT: u8 = ADDR_PINS_000;
+//Adresse avec A2, A1 et A0 au niveau haut
+const ADDR_MAX: u8 = ADDR_PINS_111;
+
+/// Cherche les DS1621 presents sur le bus : chaque adresse 0x48..=0x4F repondant a une
+/// lecture du registre de configuration est rangee dans `found`, dans l'ordre
+/// croissant. Renvoie le nombre d'adresses stockees, limite par la taille de `found`.
+///
+/// ```ignore
+/// let mut found = [Address::default(); 8];
+/// let count = ds1621::scan(&mut i2c, &mut found);
+/// ```
+pub fn scan<I2C: WriteRead>(i2c: &mut I2C, found: &mut [Address]) -> usize {
+    let mut count = 0;
+    let mut u8rd_buff: [u8; 1] = [0; 1];
+
+    for addr in ADDR_DEFAULT..=ADDR_MAX {
+        if count == found.len() {
+            break;
+        }
+        if i2c
+            .write_read(addr, &[Register::ACCESS_CONFIG], &mut u8rd_buff)
+            .is_ok()
+        {
+            found[count] = Address(addr);
+            count += 1;
+        }
+    }
+
+    count
+}
+
+//Scrutation du bit DONE : 100 x 10ms couvrent largement les 750ms d'une conversion
+#[cfg(feature = "float")]
+const CONVERSION_POLL_MS: u16 = 10;
+const CONVERSION_MAX_POLLS: u32 = 100;
+
+//Scrutation du bit NVB apres une ecriture en EEPROM (10ms au plus)
+const NV_WRITE_MAX_POLLS: u32 = 100;
+//Sans attente entre deux lectures : une lecture de la configuration dure environ
+//95us a 400kHz, soit pres de 95ms au total, bien au-dela des 10ms de l'EEPROM
+const NV_BUSY_LOOP_POLLS: u32 = 1000;
+
+impl<I2C> Ds1621<I2C> {
+    /// Temperature minimale de fonctionnement, en degres Celsius : aucun seuil ne peut
+    /// etre programme en dessous
+    pub const TEMP_MIN: f32 = TEMP_MIN;
+    /// Temperature maximale de fonctionnement, en degres Celsius : aucun seuil ne peut
+    /// etre programme au-dessus
+    pub const TEMP_MAX: f32 = TEMP_MAX;
+    /// Nombre maximal de mesures accepte par
+    /// [`read_temperature_stable`](Self::read_temperature_stable)
+    pub const MAX_STABLE_SAMPLES: u8 = MAX_STABLE_SAMPLES;
 
-impl<I2C, E> ds1621<I2C>
-where
-    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
-{
     pub fn new_default(i2c: I2C) -> Self {
-        ds1621 {
+        Ds1621 {
             i2c,
             addr: ADDR_DEFAULT,
-            mode: MODE::CONTINOUS, //By default set CONTiNOUS MODE
+            mode: Mode::Continuous, //By default set CONTINUOUS MODE
+            max_conversion_polls: CONVERSION_MAX_POLLS,
+            variant: Variant::Ds1621,
+            resolution: Resolution::Bits9,
+            cached_config: None,
+            nv_pending: false,
+            needs_settle: false,
+            verify_thresholds: false,
+            retries: 0,
+            consecutive_errors: 0,
+            nv_poll_delay: None,
+        }
+    }
+
+    /// Driver a l'adresse `a_addr`, sans acces au bus : le mode est suppose continu,
+    /// voir [`new_read`](Self::new_read)
+    pub fn new(i2c: I2C, a_addr: Address) -> Self {
+        Self::with_addr(i2c, a_addr.value())
+    }
+
+    /// Ancienne forme de [`new`](Self::new), l'adresse n'etant pas verifiee
+    #[deprecated(note = "utiliser new avec une Address, ou try_new")]
+    pub fn new_u8(i2c: I2C, a_addr: u8) -> Self {
+        Self::with_addr(i2c, a_addr)
+    }
+
+    /// Comme [`new`](Self::new), mais refuse une adresse hors de la plage 0x48..=0x4F
+    /// a laquelle le DS1621 peut repondre selon le cablage de A2-A0. Sans acces au
+    /// bus, l'erreur ne peut etre que [`Error::InvalidParameter`].
+    pub fn try_new(i2c: I2C, a_addr: u8) -> Result<Self, Error<Infallible>> {
+        match Address::from_u8(a_addr) {
+            Some(addr) => Ok(Self::new(i2c, addr)),
+            None => Err(Error::InvalidParameter),
         }
     }
 
-    pub fn new(i2c: I2C, a_addr: u8) -> Self {
-        ds1621 {
+    fn with_addr(i2c: I2C, a_addr: u8) -> Self {
+        Ds1621 {
             i2c,
             addr: a_addr,
-            mode: MODE::CONTINOUS, //By default set CONTiNOUS MODE
+            mode: Mode::Continuous, //By default set CONTINUOUS MODE
+            max_conversion_polls: CONVERSION_MAX_POLLS,
+            variant: Variant::Ds1621,
+            resolution: Resolution::Bits9,
+            cached_config: None,
+            nv_pending: false,
+            needs_settle: false,
+            verify_thresholds: false,
+            retries: 0,
+            consecutive_errors: 0,
+            nv_poll_delay: None,
         }
     }
 
-    pub fn set_convert_mode(&mut self, a_mode: MODE) -> Result<(), Error<E>> {
-        //Lire le contenu du registre de configuration
-        match self.read_config() {
-            Ok(mut conf_val) => {
-                //Ajuster le bit de mode de convertion
-                match a_mode {
-                    MODE::CONTINOUS => {
-                        conf_val |= ConfigRegBits::ONE_SHOT;
-                    }
-                    MODE::ONE_SHOT => {
-                        conf_val &= 0xFE;
-                    }
-                }
-
-                //Ecrire la config ajustee
-                return self.write_config(conf_val);
-            }
-            Err(e) => {
-                return Err(Error::I2C(e));
-            }
+    /// Driver pour un DS1631 (ou DS1631A, DS1731, DS1721) a l'adresse `a_addr`.
+    /// La resolution supposee est celle de sortie d'usine, 12 bits ; les bits
+    /// inutilises a plus basse resolution etant lus a zero, le decodage reste juste.
+    pub fn new_ds1631(i2c: I2C, a_addr: Address) -> Self {
+        Ds1621 {
+            variant: Variant::Ds1631,
+            resolution: Resolution::Bits12,
+            ..Self::new(i2c, a_addr)
         }
     }
-}
 
-impl<I2C, E> ds1621<I2C> where I2C: Read<Error = E> {}
+    /// Driver a l'adresse fixee par le niveau des broches A2, A1 et A0
+    /// (`true` = niveau haut), soit 0x48 | A2A1A0
+    pub fn from_pins(i2c: I2C, a2: bool, a1: bool, a0: bool) -> Self {
+        Self::new(i2c, Address::from_pins(a2, a1, a0))
+    }
 
-impl<I2C, E> ds1621<I2C>
-where
-    I2C: Write<Error = E>,
-{
-    pub fn write_config(&mut self, a_config: u8) -> Result<(), Error<E>> {
-        match self.i2c.write(self.addr, &[a_config]) {
-            Ok(()) => {
-                return Ok(());
-            }
-            Err(e) => {
-                return Err(Error::I2C(e));
-            }
-        }
+    /// Detruit le driver et rend le bus I2C, par exemple pour le confier a un autre driver
+    pub fn release(self) -> I2C {
+        self.i2c
     }
 
-    pub fn start_convert(&mut self) -> Result<(), E> {
-        self.i2c.write(self.addr, &[Register::START_CONVERT])
+    /// Emprunte le bus I2C, pour une transaction sans rapport avec le capteur.
+    ///
+    /// Le pointeur de registre du DS1621 peut ensuite designer un autre registre :
+    /// sans consequence pour ce driver, qui le repositionne par un `write_read` a
+    /// chaque lecture. Si la configuration du capteur est modifiee de cette facon,
+    /// appeler [`invalidate_cache`](Self::invalidate_cache).
+    pub fn inner_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
     }
 
-    pub fn stop_convert(&mut self) -> Result<(), E> {
-        self.i2c.write(self.addr, &[Register::STOP_CONVERT])
+    /// Nombre maximal de lectures du bit DONE avant d'abandonner une conversion avec
+    /// [`Error::Timeout`] (100 par defaut). [`measure_once`](Self::measure_once) attend
+    /// 10ms entre deux lectures, soit une seconde par defaut. Les mesures one shot
+    /// lisent DONE au moins deux fois, la premiere lecture apres Start Convert
+    /// pouvant etre celle de la conversion precedente.
+    pub fn set_conversion_timeout(&mut self, max_polls: u32) {
+        self.max_conversion_polls = max_polls;
     }
 
-    pub fn write_high_temperature(&mut self, a_temp: f32) -> Result<(), Error<E>> {
-        self.write_threshold_temperature(a_temp, Register::ACCESS_TH)
+    /// Composant pilote par ce driver
+    pub fn variant(&self) -> Variant {
+        self.variant
     }
 
-    pub fn write_low_temperature(&mut self, a_temp: f32) -> Result<(), Error<E>> {
-        self.write_threshold_temperature(a_temp, Register::ACCESS_TL)
+    /// Resolution utilisee pour decoder les registres de temperature
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
     }
 
-    pub fn write_threshold_temperature(&mut self, a_temp: f32, reg: u8) -> Result<(), Error<E>> {
-        if reg != Register::ACCESS_TL && reg != Register::ACCESS_TH {
-            return Err(Error::INVALID_PARAMETER);
+    /// Duree maximale d'une conversion selon la datasheet : 750ms pour le DS1621,
+    /// de 94ms (9 bits) a 750ms (12 bits) pour les DS1631/DS1721
+    pub fn max_conversion_time_ms(&self) -> u32 {
+        match (self.variant, self.resolution) {
+            (Variant::Ds1621, _) => 750,
+            (Variant::Ds1631, Resolution::Bits9) => 94,
+            (Variant::Ds1631, Resolution::Bits10) => 188,
+            (Variant::Ds1631, Resolution::Bits11) => 375,
+            (Variant::Ds1631, Resolution::Bits12) => 750,
         }
+    }
 
-        let mut wr_buff: [u8; 3] = [reg as u8, a_temp as u8, 0];
+    /// Nombre de nouvelles tentatives d'une transaction I2C en erreur (NAK sur un bus
+    /// bruite, cable long...) avant de renvoyer [`Error::I2C`], 0 par defaut
+    pub fn set_retries(&mut self, retries: u8) {
+        self.retries = retries;
+    }
 
-        //Conserver uniquement la partie entiere
-        let round = a_temp as u32;
+    /// Nombre de transactions I2C echouees depuis la derniere reussie (chaque
+    /// nouvelle tentative comptant pour une), pour decider de reinitialiser le bus ou
+    /// de signaler un capteur defaillant
+    pub fn consecutive_errors(&self) -> u32 {
+        self.consecutive_errors
+    }
 
-        if (round as f32 - a_temp).ge(&0.5_f32) == true {
-            wr_buff[2] = 0x80;
-        }
+    /// Active la relecture des seuils par
+    /// [`write_high_temperature_wait`](Self::write_high_temperature_wait) et
+    /// [`write_low_temperature_wait`](Self::write_low_temperature_wait) : une fois
+    /// l'ecriture EEPROM terminee, la valeur relue doit etre celle programmee, sinon le
+    /// seuil est reecrit une fois puis [`Error::Verify`] est renvoye. Desactive par defaut.
+    pub fn set_threshold_verify(&mut self, enabled: bool) {
+        self.verify_thresholds = enabled;
+    }
 
-        //Ecrire la commande
-        match self.i2c.write(self.addr, &wr_buff) {
-            Ok(()) => {
-                return Ok(());
-            }
-            Err(e) => {
-                return Err(Error::I2C(e));
-            }
-        }
+    /// Attente appelee entre deux lectures de NVB quand le driver attend de lui-meme
+    /// la fin d'une ecriture en EEPROM ([`write_config`](Self::write_config), mode,
+    /// polarite, [`apply_config`](Self::apply_config)...), par exemple
+    /// `|| cortex_m::asm::delay(8_000)`. L'attente est alors bornee a 100 appels, comme
+    /// pour [`wait_for_nv_write`](Self::wait_for_nv_write). Sans attente (par defaut),
+    /// NVB est relu en boucle au plus 1000 fois, de quoi couvrir les 10ms d'une
+    /// ecriture jusqu'a 400kHz.
+    pub fn set_nv_poll_delay(&mut self, delay: fn()) {
+        self.nv_poll_delay = Some(delay);
+    }
+
+    fn track_errors(&mut self, success: bool) {
+        self.consecutive_errors = if success {
+            0
+        } else {
+            self.consecutive_errors.saturating_add(1)
+        };
+    }
+
+    /// Oublie la configuration memorisee par le driver.
+    ///
+    /// La derniere valeur lue ou ecrite du registre de configuration est gardee en
+    /// cache pour que les methodes qui le modifient (mode, polarite, indicateurs...)
+    /// n'aient pas a le relire ; les indicateurs THF/TLF, qui evoluent seuls, ne sont
+    /// jamais remis a zero par ces methodes. Si le capteur a pu etre reconfigure par
+    /// ailleurs, appeler cette methode force une relecture.
+    pub fn invalidate_cache(&mut self) {
+        self.cached_config = None;
     }
 }
 
-impl<I2C, E> ds1621<I2C>
+impl<I2C, E> Ds1621<I2C>
 where
-    I2C: WriteRead<Error = E>,
+    I2C: Write<Error = E> + WriteRead<Error = E>,
 {
-    pub fn read_temperature(&mut self) -> Result<f32, E> {
-        let mut raw_read: [u8; 2] = [0; 2];
-
-        match self
-            .i2c
-            .write_read(self.addr, &[Register::TEMPERATURE], &mut raw_read)
-        {
-            Ok(()) => {
-                let mut temp: f32 = raw_read[0] as f32;
-                if (raw_read[1] != 0) {
-                    temp += 0.5;
-                }
+    /// Cree le driver et programme aussitot `config` dans le capteur, pour que le mode
+    /// memorise et celui du composant concordent des le depart.
+    pub fn new_with_config(i2c: I2C, a_addr: Address, config: Config) -> Result<Self, Error<E>> {
+        let mut sensor = Self::new(i2c, a_addr);
 
-                return Ok(temp);
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
+        sensor.write_config(config.to_bits())?;
+        sensor.mode = config.mode();
+        Ok(sensor)
     }
-    /// Methode permettant de recuperer la temperature avec une valeur precise
-    /// Contrairement à la methode read_temperature qui ne permet d'obtenir qu'une temperature
-    /// à 0.5°C pres, cette methode ci read_temperature_fine permet d'obtenir la temperature
-    /// avec une precision de l'odre de 0.1°C
-    pub fn read_temperature_fine(&mut self) -> Result<f32, E> {
-        let mut raw_read: [u8; 1] = [0; 1];
-        let mut temperature: f32 = 0.0;
 
-        match self
-            .i2c
-            .write_read(self.addr, &[Register::TEMPERATURE], &mut raw_read)
-        {
-            Ok(()) => match self.read_counter() {
-                Ok(ccounter) => match self.read_slope() {
-                    Ok(slope) => {
-                        temperature = raw_read[0] as f32;
-                        temperature -= 0.25;
-                        temperature += ((slope as f32 - ccounter as f32) / slope as f32);
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
-                },
-                Err(e) => {
-                    return Err(e);
-                }
-            },
-            Err(e) => return Err(e),
-        }
+    pub fn set_convert_mode(&mut self, a_mode: Mode) -> Result<(), Error<E>> {
+        //Ajuster le bit de mode de convertion
+        self.modify_config(|conf_val| a_mode.apply(conf_val))?;
 
-        return Ok(temperature);
+        //Memoriser le mode une fois la config ecrite
+        self.mode = a_mode;
+        self.needs_settle = a_mode == Mode::Continuous;
+        Ok(())
     }
 
-    pub fn read_config(&mut self) -> Result<u8, E> {
-        let mut u8rd_buff: [u8; 1] = [0; 1];
-
-        match self
-            .i2c
-            .write_read(self.addr, &[Register::ACCESS_CONFIG], &mut u8rd_buff)
-        {
-            Ok(()) => {
-                return Ok(u8rd_buff[0]);
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
+    /// Passe en mode de conversion continue puis lance les conversions : sans
+    /// Start Convert le capteur reste a l'arret et le registre de temperature
+    /// garde sa derniere valeur.
+    pub fn start_continuous(&mut self) -> Result<(), Error<E>> {
+        self.set_convert_mode(Mode::Continuous)?;
+        self.start_convert()
     }
 
-    pub fn read_counter(&mut self) -> Result<u8, E> {
-        let mut u8rd_buff: [u8; 1] = [0; 1];
+    /// Choisit le niveau actif de la sortie thermostat Tout (bit POL) :
+    /// `true` pour actif a l'etat haut, `false` pour actif a l'etat bas.
+    /// Les autres bits de configuration, dont le mode, sont conserves.
+    pub fn set_polarity(&mut self, active_high: bool) -> Result<(), Error<E>> {
+        self.modify_config(|conf_val| config_with_polarity(conf_val, active_high))
+    }
 
-        match self
-            .i2c
-            .write_read(self.addr, &[Register::READ_COUNTER], &mut u8rd_buff)
-        {
-            Ok(()) => {
-                return Ok(u8rd_buff[0]);
-            }
-            Err(e) => {
-                return Err(e);
+    /// Selectionne la resolution de conversion (bits R1/R0) sur les DS1631/DS1721.
+    /// Un DS1621 n'accepte que [`Resolution::Bits9`], toute autre valeur renvoie
+    /// [`Error::InvalidParameter`].
+    pub fn set_resolution(&mut self, bits: Resolution) -> Result<(), Error<E>> {
+        match self.variant {
+            Variant::Ds1621 if bits != Resolution::Bits9 => {
+                return Err(Error::InvalidParameter);
             }
+            Variant::Ds1621 => {}
+            Variant::Ds1631 => self.modify_config(|conf_val| {
+                (conf_val & !(ConfigRegBits::R1 | ConfigRegBits::R0)) | bits.config_bits()
+            })?,
         }
+
+        self.resolution = bits;
+        Ok(())
     }
 
-    pub fn read_slope(&mut self) -> Result<u8, E> {
-        let mut u8rd_buff: [u8; 1] = [0; 1];
+    /// Remet a zero les indicateurs verrouilles THF et TLF en conservant
+    /// les autres bits de configuration (mode, polarite)
+    pub fn clear_flags(&mut self) -> Result<(), Error<E>> {
+        self.modify_config(|conf_val| conf_val & !ConfigRegBits::FLAGS)
+    }
 
-        match self
-            .i2c
-            .write_read(self.addr, &[Register::READ_SLOPE], &mut u8rd_buff)
-        {
-            Ok(()) => {
-                return Ok(u8rd_buff[0]);
-            }
-            Err(e) => {
-                return Err(e);
-            }
+    /// Lit les indicateurs THF et TLF et remet a zero celui qui est leve, en
+    /// renvoyant le franchissement correspondant, ou `None` si aucun seuil n'a ete
+    /// franchi depuis le dernier appel. Appelee en boucle, elle signale chaque
+    /// franchissement une seule fois. Si les deux indicateurs sont leves,
+    /// [`Crossing::High`] est renvoye d'abord et TLF reste leve pour l'appel suivant.
+    pub fn check_crossings(&mut self) -> Result<Option<Crossing>, Error<E>> {
+        //Les indicateurs evoluent seuls : la configuration en cache ne suffit pas
+        let conf_val = self.read_config()?;
+
+        let (crossing, flag) = if conf_val & ConfigRegBits::THF != 0 {
+            (Crossing::High, ConfigRegBits::THF)
+        } else if conf_val & ConfigRegBits::TLF != 0 {
+            (Crossing::Low, ConfigRegBits::TLF)
+        } else {
+            return Ok(None);
+        };
+
+        match self.write_config(conf_val & !flag) {
+            Ok(()) => Ok(Some(crossing)),
+            Err(e) => Err(e),
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Programme la configuration puis les seuils TH et TL, en attendant la fin de
+    /// chaque ecriture en EEPROM avant la suivante. Les indicateurs THF/TLF sont remis
+    /// a zero et la resolution courante est conservee sur DS1631.
+    /// Sans rien ecrire, renvoie [`Error::OutOfRange`] si un seuil est hors de
+    /// -55..=125°C et [`Error::InvalidParameter`] si `high` n'est pas strictement
+    /// au-dessus de `low`.
+    pub fn apply_config(&mut self, cfg: FullConfig) -> Result<(), Error<E>> {
+        let in_range =
+            |t: Temperature| (TEMP_MIN_HALVES..=TEMP_MAX_HALVES).contains(&t.half_degrees());
+        if !in_range(cfg.high) || !in_range(cfg.low) {
+            return Err(Error::OutOfRange);
+        }
+        if cfg.high <= cfg.low {
+            return Err(Error::InvalidParameter);
+        }
+
+        let conf_val = config_with_polarity(
+            cfg.mode.apply(self.resolution.config_bits()),
+            cfg.active_high,
+        );
+        self.write_config(conf_val)?;
+        self.mode = cfg.mode;
+        self.needs_settle = cfg.mode == Mode::Continuous;
+
+        self.wait_nv_ready()?;
+        self.write_threshold_half_degrees(cfg.high.half_degrees(), Register::ACCESS_TH)?;
+        self.wait_nv_ready()?;
+        self.write_threshold_half_degrees(cfg.low.half_degrees(), Register::ACCESS_TL)
+    }
+
+    #[cfg(feature = "float")]
+    /// Programme la fenetre du thermostat : Tout s'active au-dessus de `high` et ne
+    /// se desactive qu'en dessous de `low` (par exemple ventilateur au-dessus de 30°C,
+    /// arrete sous 25°C). Chaque seuil est ecrit une fois l'ecriture en EEPROM
+    /// precedente terminee (bit NVB). Sans rien ecrire, renvoie [`Error::OutOfRange`]
+    /// pour un seuil hors de -55..=125°C et [`Error::InvalidParameter`] si la fenetre
+    /// est inversee ou nulle une fois arrondie au demi-degre.
+    pub fn configure_thermostat(&mut self, high: f32, low: f32) -> Result<(), Error<E>> {
+        match (encode_temp(high), encode_temp(low)) {
+            (Some(_), Some(_)) if half_degrees(high) > half_degrees(low) => {}
+            (Some(_), Some(_)) => return Err(Error::InvalidParameter),
+            _ => return Err(Error::OutOfRange),
+        }
+
+        self.wait_nv_ready()?;
+        self.write_high_temperature(high)?;
+        self.wait_nv_ready()?;
+        self.write_low_temperature(low)?;
+        Ok(())
+    }
+
+    /// Equivalent sans virgule flottante de
+    /// [`configure_thermostat`](Self::configure_thermostat), seuils en demi-degres,
+    /// chacun ecrit une fois l'ecriture en EEPROM precedente terminee.
+    /// Sans rien ecrire, renvoie [`Error::OutOfRange`] pour un seuil hors de
+    /// -55..=125°C et [`Error::InvalidParameter`] si `high` n'est pas strictement
+    /// au-dessus de `low`.
+    pub fn write_thresholds_raw(&mut self, high: i16, low: i16) -> Result<(), Error<E>> {
+        let in_range = |halves: i16| (TEMP_MIN_HALVES..=TEMP_MAX_HALVES).contains(&halves);
+        if !in_range(high) || !in_range(low) {
+            return Err(Error::OutOfRange);
+        }
+        if high <= low {
+            return Err(Error::InvalidParameter);
+        }
+
+        self.wait_nv_ready()?;
+        self.write_threshold_half_degrees(high, Register::ACCESS_TH)?;
+        self.wait_nv_ready()?;
+        self.write_threshold_half_degrees(low, Register::ACCESS_TL)
+    }
+
+    /// Ramene le capteur dans un etat connu : conversion continue, POL=0 et
+    /// indicateurs THF/TLF remis a zero. La resolution courante du driver est
+    /// conservee sur DS1631. Utile au demarrage, quand un programme precedent a pu
+    /// laisser le capteur en mode one shot avec des indicateurs verrouilles.
+    pub fn reset_config(&mut self) -> Result<(), Error<E>> {
+        self.write_config(self.resolution.config_bits())?;
+        self.mode = Mode::Continuous;
+        self.needs_settle = true;
+        Ok(())
+    }
+
+    /// Ecrit la configuration puis la relit pour verifier les bits reellement
+    /// programmables (POL, 1SHOT et R1/R0 sur DS1631) ; DONE, NVB, THF et TLF evoluent seuls et ne
+    /// sont pas compares. Renvoie [`Error::Verify`] en cas de difference, ce qui
+    /// permet de detecter un cablage douteux lors de la mise au point.
+    pub fn write_config_verified(&mut self, a_config: u8) -> Result<(), Error<E>> {
+        self.write_config(a_config)?;
+
+        match self.read_config() {
+            Ok(conf_val) if (conf_val ^ a_config) & self.variant.writable_bits() == 0 => Ok(()),
+            Ok(_) => Err(Error::Verify),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit la configuration et n'ecrit `desired` que si POL ou 1SHOT different, les
+    /// autres bits lus (indicateurs, resolution sur DS1631) etant reecrits tels quels.
+    /// Renvoie `true` si une ecriture a eu lieu : appelee a chaque demarrage, elle
+    /// evite d'user l'EEPROM en reprogrammant une configuration identique.
+    pub fn ensure_config(&mut self, desired: Config) -> Result<bool, Error<E>> {
+        let conf_val = self.read_config()?;
+        self.mode = Mode::from_config(conf_val);
+
+        let wanted = desired.to_bits() & ConfigRegBits::WRITABLE;
+        if conf_val & ConfigRegBits::WRITABLE == wanted {
+            return Ok(false);
+        }
+
+        match self.write_config((conf_val & !ConfigRegBits::WRITABLE) | wanted) {
+            Ok(()) => {
+                self.mode = desired.mode();
+                self.needs_settle = self.mode == Mode::Continuous;
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    //Lecture-modification-ecriture du registre de configuration : tous les bits
+    //que `f` ne touche pas sont reecrits a l'identique. La lecture est evitee
+    //quand la configuration est en cache (voir invalidate_cache) ; THF et TLF ont
+    //pu se lever depuis la lecture en cache et sont alors ecrits a 1, ce qui les
+    //laisse inchanges : seul clear_flags les remet a zero
+    fn modify_config<F>(&mut self, f: F) -> Result<(), Error<E>>
+    where
+        F: FnOnce(u8) -> u8,
+    {
+        let polled = self.nv_pending;
+        self.wait_nv_ready()?;
+
+        let conf_val = match self.cached_config {
+            //Relue a l'instant par la scrutation de NVB
+            Some(conf_val) if polled => conf_val,
+            Some(conf_val) => conf_val | ConfigRegBits::FLAGS,
+            None => self.read_config()?,
+        };
+
+        self.write_config_raw(f(conf_val))
+    }
+
+    /// Ecrit le registre de configuration. Si une ecriture en EEPROM (configuration,
+    /// TH ou TL) vient d'etre lancee, attend d'abord que le bit NVB retombe : le
+    /// capteur ignorerait sinon la nouvelle valeur. Renvoie [`Error::Timeout`] si NVB
+    /// reste leve, voir [`set_nv_poll_delay`](Self::set_nv_poll_delay).
+    pub fn write_config(&mut self, a_config: u8) -> Result<(), Error<E>> {
+        self.wait_nv_ready()?;
+        self.write_config_raw(a_config)
+    }
+
+    //Scrutation de NVB, seulement si une ecriture en EEPROM est peut-etre en cours
+    fn wait_nv_ready(&mut self) -> Result<(), Error<E>> {
+        if !self.nv_pending {
+            return Ok(());
+        }
+
+        match self.nv_poll_delay {
+            Some(delay) => self.poll_nv_write(NV_WRITE_MAX_POLLS, delay),
+            None => self.poll_nv_write(NV_BUSY_LOOP_POLLS, || {}),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    /// Lance une conversion, attend sa fin puis lit la temperature (mode one shot).
+    /// Voir [`wait_for_conversion`](Self::wait_for_conversion) pour `delay` ; un bit
+    /// DONE lu aussitot apres Start Convert, qui peut encore etre celui de la
+    /// conversion precedente, n'est pas pris en compte.
+    pub fn read_temperature_one_shot<F>(&mut self, delay: F) -> Result<f32, Error<E>>
+    where
+        F: FnMut(),
+    {
+        self.start_convert()?;
+        self.wait_for_fresh_conversion(delay)?;
+        self.read_temperature()
+    }
+
+    #[cfg(feature = "float")]
+    /// Realise une mesure unique : passe en mode one shot, lance la conversion, attend
+    /// le bit DONE en dormant via `delay` entre deux lectures, lit la temperature puis
+    /// restaure le mode de conversion precedent.
+    /// Renvoie [`Error::Timeout`] si la conversion ne se termine pas a temps
+    /// (voir [`set_conversion_timeout`](Self::set_conversion_timeout)).
+    pub fn measure_once<D>(&mut self, delay: &mut D) -> Result<f32, Error<E>>
+    where
+        D: DelayMs<u16>,
+    {
+        let was_continuous = match self.get_convert_mode() {
+            Ok(Mode::Continuous) => true,
+            Ok(Mode::OneShot) => false,
+            Err(e) => return Err(e),
+        };
+
+        if was_continuous {
+            self.set_convert_mode(Mode::OneShot)?;
+        }
+
+        let result = self.read_temperature_one_shot(|| delay.delay_ms(CONVERSION_POLL_MS));
+
+        if was_continuous {
+            self.set_convert_mode(Mode::Continuous)?;
+        }
+
+        result
+    }
+
+    #[cfg(feature = "float")]
+    /// Mesure unique en mode one shot avec une seule attente de
+    /// [`max_conversion_time_ms`](Self::max_conversion_time_ms) au lieu de scruter DONE
+    /// toutes les 10ms, ce qui reduit le trafic sur le bus. Si DONE n'est pas leve a
+    /// l'issue du delai (temporisation imprecise), la scrutation prend le relais comme
+    /// dans [`measure_once`](Self::measure_once).
+    pub fn read_one_shot_blocking<D>(&mut self, delay: &mut D) -> Result<f32, Error<E>>
+    where
+        D: DelayMs<u16>,
+    {
+        self.start_convert()?;
+        delay.delay_ms(self.max_conversion_time_ms() as u16);
+        self.wait_for_conversion(|| delay.delay_ms(CONVERSION_POLL_MS))?;
+        self.read_temperature()
+    }
+
+    #[cfg(feature = "float")]
+    /// Renvoie la derniere temperature disponible sans changer le mode programme :
+    /// en mode continu le registre est simplement relu, en mode one shot une
+    /// conversion est lancee et attendue comme dans [`measure_once`](Self::measure_once).
+    pub fn read_latest<D>(&mut self, delay: &mut D) -> Result<f32, Error<E>>
+    where
+        D: DelayMs<u16>,
+    {
+        match self.get_convert_mode() {
+            Ok(Mode::Continuous) => self.read_temperature(),
+            Ok(Mode::OneShot) => {
+                self.read_temperature_one_shot(|| delay.delay_ms(CONVERSION_POLL_MS))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    /// Comme [`write_high_temperature`](Self::write_high_temperature), puis attend la fin
+    /// de l'ecriture en EEPROM (voir [`wait_for_nv_write`](Self::wait_for_nv_write)) et
+    /// relit le seuil si [`set_threshold_verify`](Self::set_threshold_verify) est actif
+    pub fn write_high_temperature_wait<F>(&mut self, a_temp: f32, delay: F) -> Result<f32, Error<E>>
+    where
+        F: FnMut(),
+    {
+        self.write_threshold_wait(a_temp, Register::ACCESS_TH, delay)
+    }
+
+    #[cfg(feature = "float")]
+    /// Comme [`write_low_temperature`](Self::write_low_temperature), puis attend la fin
+    /// de l'ecriture en EEPROM (voir [`wait_for_nv_write`](Self::wait_for_nv_write)) et
+    /// relit le seuil si [`set_threshold_verify`](Self::set_threshold_verify) est actif
+    pub fn write_low_temperature_wait<F>(&mut self, a_temp: f32, delay: F) -> Result<f32, Error<E>>
+    where
+        F: FnMut(),
+    {
+        self.write_threshold_wait(a_temp, Register::ACCESS_TL, delay)
+    }
+
+    #[cfg(feature = "float")]
+    //Ecriture d'un seuil puis attente de l'EEPROM ; avec la verification active, le
+    //seuil est relu et reecrit une seule fois s'il differe de la valeur programmee
+    fn write_threshold_wait<F>(
+        &mut self,
+        a_temp: f32,
+        reg: u8,
+        mut delay: F,
+    ) -> Result<f32, Error<E>>
+    where
+        F: FnMut(),
+    {
+        let programmed = self.write_threshold_temperature(a_temp, reg)?;
+        self.wait_for_nv_write(&mut delay)?;
+        if !self.verify_thresholds || self.threshold_matches(reg, programmed)? {
+            return Ok(programmed);
+        }
+
+        self.write_threshold_temperature(a_temp, reg)?;
+        self.wait_for_nv_write(&mut delay)?;
+        match self.threshold_matches(reg, programmed) {
+            Ok(true) => Ok(programmed),
+            Ok(false) => Err(Error::Verify),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    fn threshold_matches(&mut self, reg: u8, programmed: f32) -> Result<bool, Error<E>> {
+        match self.read_register_raw(reg) {
+            Ok(raw) => Ok(decode_temp(raw[0], raw[1], Resolution::Bits9) == programmed),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<I2C, E> Ds1621<I2C>
+where
+    I2C: Write<Error = E>,
+{
+    //Ecriture sur le bus, retentee jusqu'a `retries` fois en cas d'erreur
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error<E>> {
+        let mut attempt = 0;
+        loop {
+            let result = self.i2c.write(self.addr, bytes);
+            self.track_errors(result.is_ok());
+            match result {
+                Ok(()) => return Ok(()),
+                Err(_) if attempt < self.retries => attempt += 1,
+                Err(e) => return Err(Error::I2C(e)),
+            }
+        }
+    }
+
+    fn write_config_raw(&mut self, a_config: u8) -> Result<(), Error<E>> {
+        //La commande ACCESS_CONFIG doit preceder l'octet de configuration
+        match self.write_bytes(&[Register::ACCESS_CONFIG, a_config]) {
+            Ok(()) => {
+                self.cached_config = Some(a_config);
+                self.nv_pending = true;
+                Ok(())
+            }
+            Err(e) => {
+                //L'etat du registre est incertain apres un echec
+                self.cached_config = None;
+                Err(e)
+            }
+        }
+    }
+
+    pub fn start_convert(&mut self) -> Result<(), Error<E>> {
+        match self.write_bytes(&[self.variant.start_convert_command()]) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn stop_convert(&mut self) -> Result<(), Error<E>> {
+        match self.write_bytes(&[Register::STOP_CONVERT]) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Arrete les conversions continues : entre deux conversions le capteur ne
+    /// consomme plus que son courant de veille (1µA typique contre 1mA en conversion).
+    /// En mode one shot le capteur est deja en veille hors conversion et la
+    /// commande n'interrompt au plus que la conversion en cours.
+    pub fn shutdown(&mut self) -> Result<(), Error<E>> {
+        self.stop_convert()
+    }
+
+    /// Reprend apres [`shutdown`](Self::shutdown) selon le mode memorise : relance les
+    /// conversions en mode continu, ne fait rien en mode one shot ou chaque mesure
+    /// est lancee par [`start_convert`](Self::start_convert).
+    pub fn wake(&mut self) -> Result<(), Error<E>> {
+        match self.mode {
+            Mode::Continuous => {
+                self.start_convert()?;
+                self.needs_settle = true;
+                Ok(())
+            }
+            Mode::OneShot => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    /// Programme le seuil haut TH au demi-degre le plus proche, voir
+    /// [`write_threshold_temperature`](Self::write_threshold_temperature)
+    pub fn write_high_temperature(&mut self, a_temp: f32) -> Result<f32, Error<E>> {
+        self.write_threshold_temperature(a_temp, Register::ACCESS_TH)
+    }
+
+    #[cfg(feature = "float")]
+    /// Programme le seuil bas TL au demi-degre le plus proche, voir
+    /// [`write_threshold_temperature`](Self::write_threshold_temperature)
+    pub fn write_low_temperature(&mut self, a_temp: f32) -> Result<f32, Error<E>> {
+        self.write_threshold_temperature(a_temp, Register::ACCESS_TL)
+    }
+
+    #[cfg(feature = "float")]
+    /// Programme le seuil `reg` (TH ou TL) au demi-degre le plus proche et renvoie la
+    /// valeur reellement programmee (25.3 => 25.5).
+    /// Renvoie [`Error::InvalidRegister`] pour un autre registre et [`Error::OutOfRange`]
+    /// pour une temperature hors de la plage de fonctionnement -55..=125°C.
+    pub fn write_threshold_temperature(&mut self, a_temp: f32, reg: u8) -> Result<f32, Error<E>> {
+        self.write_threshold_rounded(a_temp, reg, RoundMode::Nearest)
+    }
+
+    #[cfg(feature = "float")]
+    /// Comme [`write_threshold_temperature`](Self::write_threshold_temperature), avec
+    /// l'arrondi choisi : un seuil haut arrondi vers le bas ou un seuil bas arrondi
+    /// vers le haut ne declenche jamais plus tard que la valeur demandee.
+    pub fn write_threshold_rounded(
+        &mut self,
+        a_temp: f32,
+        reg: u8,
+        round: RoundMode,
+    ) -> Result<f32, Error<E>> {
+        if reg != Register::ACCESS_TL && reg != Register::ACCESS_TH {
+            return Err(Error::InvalidRegister);
+        }
+
+        match encode_temp_rounded(a_temp, round) {
+            Some(bytes) => {
+                self.write_register_raw(reg, bytes)?;
+                Ok(decode_temp(bytes[0], bytes[1], Resolution::Bits9))
+            }
+            None => Err(Error::OutOfRange),
+        }
+    }
+
+    /// Equivalent sans virgule flottante de
+    /// [`write_threshold_temperature`](Self::write_threshold_temperature) :
+    /// la temperature est exprimee en demi-degres (51 => 25.5°C)
+    pub fn write_threshold_half_degrees(&mut self, halves: i16, reg: u8) -> Result<(), Error<E>> {
+        if reg != Register::ACCESS_TL && reg != Register::ACCESS_TH {
+            return Err(Error::InvalidRegister);
+        }
+
+        match encode_half_degrees(halves as i32) {
+            Some(bytes) => self.write_register_raw(reg, bytes),
+            None => Err(Error::OutOfRange),
+        }
+    }
+
+    /// Programme le seuil haut TH a partir de millidegres (25_500 => 25.5°C), arrondis
+    /// au demi-degre le plus proche sans virgule flottante. Renvoie le seuil
+    /// effectivement programme, en millidegres, ou [`Error::OutOfRange`] hors de
+    /// -55..=125°C.
+    pub fn write_high_temperature_mc(&mut self, mc: i32) -> Result<i32, Error<E>> {
+        self.write_threshold_mc(mc, Register::ACCESS_TH)
+    }
+
+    /// Programme le seuil bas TL a partir de millidegres, voir
+    /// [`write_high_temperature_mc`](Self::write_high_temperature_mc)
+    pub fn write_low_temperature_mc(&mut self, mc: i32) -> Result<i32, Error<E>> {
+        self.write_threshold_mc(mc, Register::ACCESS_TL)
+    }
+
+    fn write_threshold_mc(&mut self, mc: i32, reg: u8) -> Result<i32, Error<E>> {
+        //Plage verifiee avant l'arrondi, comme pour les seuils en virgule flottante
+        if !(TEMP_MIN_MC..=TEMP_MAX_MC).contains(&mc) {
+            return Err(Error::OutOfRange);
+        }
+
+        let halves = half_degrees_from_mc(mc);
+
+        match encode_half_degrees(halves) {
+            Some(bytes) => match self.write_register_raw(reg, bytes) {
+                Ok(()) => Ok(halves * 500),
+                Err(e) => Err(e),
+            },
+            None => Err(Error::OutOfRange),
+        }
+    }
+
+    /// Ecrit tels quels les deux octets du registre TH, sans conversion flottante
+    pub fn write_th_raw(&mut self, bytes: [u8; 2]) -> Result<(), Error<E>> {
+        self.write_register_raw(Register::ACCESS_TH, bytes)
+    }
+
+    /// Ecrit tels quels les deux octets du registre TL, sans conversion flottante
+    pub fn write_tl_raw(&mut self, bytes: [u8; 2]) -> Result<(), Error<E>> {
+        self.write_register_raw(Register::ACCESS_TL, bytes)
+    }
+
+    fn write_register_raw(&mut self, reg: u8, bytes: [u8; 2]) -> Result<(), Error<E>> {
+        match self.write_bytes(&[reg, bytes[0], bytes[1]]) {
+            Ok(()) => {
+                self.nv_pending = true;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<I2C, E> Ds1621<I2C>
+where
+    I2C: WriteRead<Error = E>,
+{
+    //Octet de commande puis lecture, retentes jusqu'a `retries` fois en cas d'erreur
+    fn write_read_bytes(&mut self, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Error<E>> {
+        let mut attempt = 0;
+        loop {
+            let result = self.i2c.write_read(self.addr, bytes, buffer);
+            self.track_errors(result.is_ok());
+            match result {
+                Ok(()) => return Ok(()),
+                Err(_) if attempt < self.retries => attempt += 1,
+                Err(e) => return Err(Error::I2C(e)),
+            }
+        }
+    }
+
+    /// Comme [`new`](Self::new), mais lit la configuration pour partir du mode
+    /// reellement programme : au demarrage le DS1621 reprend celui enregistre en
+    /// EEPROM, pas forcement le mode continu
+    pub fn new_read(i2c: I2C, a_addr: Address) -> Result<Self, Error<E>> {
+        let mut sensor = Self::new(i2c, a_addr);
+        let conf_val = sensor.read_config()?;
+        sensor.mode = Mode::from_config(conf_val);
+        Ok(sensor)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn read_temperature(&mut self) -> Result<f32, Error<E>> {
+        self.read_temperature_register(Register::TEMPERATURE)
+    }
+
+    #[cfg(feature = "float")]
+    /// Comme [`read_temperature`](Self::read_temperature), mais juste apres un passage
+    /// en mode continu attend d'abord une duree de conversion
+    /// ([`max_conversion_time_ms`](Self::max_conversion_time_ms)) pour ne pas renvoyer
+    /// une valeur anterieure. Des la premiere lecture reussie de la temperature, par
+    /// cette methode ou une autre, les suivantes sont immediates.
+    pub fn read_temperature_settled<D>(&mut self, delay: &mut D) -> Result<f32, Error<E>>
+    where
+        D: DelayMs<u16>,
+    {
+        if self.needs_settle {
+            delay.delay_ms(self.max_conversion_time_ms() as u16);
+        }
+
+        self.read_temperature()
+    }
+
+    #[cfg(feature = "float")]
+    /// Lit la temperature en degres Fahrenheit
+    pub fn read_temperature_fahrenheit(&mut self) -> Result<f32, Error<E>> {
+        match self.read_temperature() {
+            Ok(celsius) => Ok(celsius * 9.0 / 5.0 + 32.0),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    /// Lit la temperature en kelvins
+    pub fn read_temperature_kelvin(&mut self) -> Result<f32, Error<E>> {
+        match self.read_temperature() {
+            Ok(celsius) => Ok(celsius + 273.15),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    /// Iterateur infini de lectures en mode continu : chaque appel a `next()`
+    /// effectue une lecture I2C bloquante via [`read_temperature`](Self::read_temperature).
+    ///
+    /// ```ignore
+    /// for temp in sensor.readings().take(10) { ... }
+    /// ```
+    pub fn readings(&mut self) -> Readings<'_, I2C> {
+        Readings { sensor: self }
+    }
+
+    #[cfg(feature = "float")]
+    /// Prend jusqu'a `n` mesures espacees de `delay_ms` et les range dans `out`, sans
+    /// allocation (pour une moyenne par exemple). Renvoie le nombre de mesures
+    /// stockees, limite par la taille de `out` ; s'arrete a la premiere erreur de bus.
+    pub fn sample_n<D>(
+        &mut self,
+        n: usize,
+        delay_ms: u32,
+        delay: &mut D,
+        out: &mut [f32],
+    ) -> Result<usize, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let count = n.min(out.len());
+
+        for (i, slot) in out[..count].iter_mut().enumerate() {
+            if i != 0 {
+                delay.delay_ms(delay_ms);
+            }
+            *slot = self.read_temperature()?;
+        }
+
+        Ok(count)
+    }
+
+    #[cfg(feature = "float")]
+    /// Prend `samples` mesures, en appelant `delay` entre deux lectures, et renvoie
+    /// leur mediane (moyenne des deux valeurs centrales pour un nombre pair) : une
+    /// valeur aberrante isolee, due a un parasite sur le bus, est ainsi ecartee sans
+    /// fausser le resultat comme le ferait une moyenne. Sans allocation, renvoie
+    /// [`Error::InvalidParameter`] si `samples` vaut 0 ou depasse
+    /// [`MAX_STABLE_SAMPLES`](Self::MAX_STABLE_SAMPLES).
+    pub fn read_temperature_stable<F>(&mut self, samples: u8, mut delay: F) -> Result<f32, Error<E>>
+    where
+        F: FnMut(),
+    {
+        if samples == 0 || samples > Self::MAX_STABLE_SAMPLES {
+            return Err(Error::InvalidParameter);
+        }
+
+        //Mesures brutes a la resolution courante, triees sans passer par les flottants
+        let mut raw = [0i16; MAX_STABLE_SAMPLES as usize];
+        let raw = &mut raw[..samples as usize];
+        for (i, slot) in raw.iter_mut().enumerate() {
+            if i != 0 {
+                delay();
+            }
+            let raw_read = self.read_register_raw(Register::TEMPERATURE)?;
+            *slot = decode_raw(raw_read[0], raw_read[1], self.resolution);
+        }
+        raw.sort_unstable();
+
+        let mid = raw.len() / 2;
+        let median = if raw.len() % 2 == 0 {
+            (raw[mid - 1] as f32 + raw[mid] as f32) / 2.0
+        } else {
+            raw[mid] as f32
+        };
+        Ok(median / (1 << self.resolution.fraction_bits()) as f32)
+    }
+
+    #[cfg(feature = "float")]
+    /// Relit le seuil haut TH programme dans le capteur
+    pub fn read_high_temperature(&mut self) -> Result<f32, Error<E>> {
+        self.read_temperature_register(Register::ACCESS_TH)
+    }
+
+    #[cfg(feature = "float")]
+    /// Relit le seuil bas TL programme dans le capteur
+    pub fn read_low_temperature(&mut self) -> Result<f32, Error<E>> {
+        self.read_temperature_register(Register::ACCESS_TL)
+    }
+
+    #[cfg(feature = "float")]
+    /// Relit les deux seuils du thermostat, renvoyes sous la forme `(TH, TL)`
+    pub fn read_thresholds(&mut self) -> Result<(f32, f32), Error<E>> {
+        let high = self.read_high_temperature()?;
+        let low = self.read_low_temperature()?;
+        Ok((high, low))
+    }
+
+    #[cfg(feature = "float")]
+    /// Compare `t` aux seuils TH et TL programmes, comme le ferait le capteur pour
+    /// les indicateurs THF/TLF. Permet de reproduire le comportement de Tout en
+    /// logiciel sur les cartes ou cette broche n'est pas cablee.
+    pub fn thermostat_state(&mut self, t: f32) -> Result<ThermostatState, Error<E>> {
+        let (high, low) = self.read_thresholds()?;
+
+        if t >= high {
+            Ok(ThermostatState::Above)
+        } else if t <= low {
+            Ok(ThermostatState::Below)
+        } else {
+            Ok(ThermostatState::Within)
+        }
+    }
+
+    #[cfg(feature = "float")]
+    //Lecture d'un registre de temperature sur 2 octets (TEMPERATURE, TH ou TL)
+    fn read_temperature_register(&mut self, reg: u8) -> Result<f32, Error<E>> {
+        match self.read_register_raw(reg) {
+            Ok(raw_read) => Ok(decode_temp(raw_read[0], raw_read[1], self.resolution)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Indique si la temperature est negative (-0.5°C ou moins) en ne lisant que
+    /// l'octet de poids fort du registre, sans decodage
+    pub fn is_below_freezing(&mut self) -> Result<bool, Error<E>> {
+        let mut u8rd_buff: [u8; 1] = [0; 1];
+
+        match self.write_read_bytes(&[Register::TEMPERATURE], &mut u8rd_buff) {
+            Ok(()) => Ok(u8rd_buff[0] & 0x80 != 0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit la temperature en virgule fixe signee Q8.8 (25.5°C => 0x1980), sans perte
+    /// pour le format du registre ; les bits sous la resolution courante sont a zero
+    pub fn read_temperature_q8_8(&mut self) -> Result<i16, Error<E>> {
+        match self.read_register_raw(Register::TEMPERATURE) {
+            Ok(raw_read) => Ok(decode_raw(raw_read[0], raw_read[1], self.resolution)
+                << (8 - self.resolution.fraction_bits())),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit la temperature en millidegres Celsius, sans virgule flottante (25.5°C => 25500)
+    pub fn read_temperature_millicelsius(&mut self) -> Result<i32, Error<E>> {
+        match self.read_register_raw(Register::TEMPERATURE) {
+            Ok(raw_read) => Ok(decode_millicelsius(
+                raw_read[0],
+                raw_read[1],
+                self.resolution,
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit la temperature en demi-degres (25.5°C => 51), sans virgule flottante.
+    /// Les decimales au-dela du demi-degre (DS1631 en 10 a 12 bits) sont tronquees vers -inf.
+    pub fn read_temperature_raw(&mut self) -> Result<i16, Error<E>> {
+        match self.read_register_raw(Register::TEMPERATURE) {
+            Ok(raw_read) => Ok(decode_raw(raw_read[0], raw_read[1], Resolution::Bits9)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Copie dans `buf` les deux octets du registre de temperature (MSB, LSB), sans
+    /// aucun decodage, pour un decodage personnalise ou un journal brut
+    pub fn read_temperature_bytes(&mut self, buf: &mut [u8; 2]) -> Result<(), Error<E>> {
+        match self.read_register_raw(Register::TEMPERATURE) {
+            Ok(raw_read) => {
+                *buf = raw_read;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit tels quels les deux octets du registre TH (MSB, LSB)
+    pub fn read_th_raw(&mut self) -> Result<[u8; 2], Error<E>> {
+        self.read_register_raw(Register::ACCESS_TH)
+    }
+
+    /// Lit tels quels les deux octets du registre TL (MSB, LSB)
+    pub fn read_tl_raw(&mut self) -> Result<[u8; 2], Error<E>> {
+        self.read_register_raw(Register::ACCESS_TL)
+    }
+
+    fn read_register_raw(&mut self, reg: u8) -> Result<[u8; 2], Error<E>> {
+        let mut raw_read: [u8; 2] = [0; 2];
+
+        match self.write_read_bytes(&[reg], &mut raw_read) {
+            Ok(()) => {
+                //Premiere lecture de la temperature : read_temperature_settled n'a
+                //plus a attendre
+                if reg == Register::TEMPERATURE {
+                    self.needs_settle = false;
+                }
+                Ok(raw_read)
+            }
+            Err(e) => Err(e),
+        }
+    }
+    #[cfg(feature = "float")]
+    /// Methode permettant de recuperer la temperature avec une valeur precise
+    /// Contrairement à la methode read_temperature qui ne permet d'obtenir qu'une temperature
+    /// à 0.5°C pres, cette methode ci read_temperature_fine permet d'obtenir la temperature
+    /// avec une precision de l'odre de 0.1°C
+    #[deprecated(note = "utiliser read_temperature_high_res")]
+    pub fn read_temperature_fine(&mut self) -> Result<f32, Error<E>> {
+        self.read_temperature_high_res()
+    }
+
+    #[cfg(feature = "float")]
+    /// Lecture haute resolution de la temperature a partir des compteurs internes :
+    /// `T = T_read - 0.25 + (COUNT_PER_C - COUNT_REMAIN) / COUNT_PER_C`
+    ///
+    /// D'apres la datasheet, ce calcul n'est valable qu'en mode one shot, apres la fin
+    /// d'une conversion (bit DONE) : en mode continu les compteurs peuvent appartenir
+    /// a une conversion differente de celle du registre de temperature.
+    /// Les DS1631/DS1721 n'ont pas ces compteurs : la temperature y est lue comme par
+    /// [`read_temperature`](Self::read_temperature), a la resolution programmee
+    /// (voir [`set_resolution`](Self::set_resolution)).
+    pub fn read_temperature_high_res(&mut self) -> Result<f32, Error<E>> {
+        if self.variant == Variant::Ds1631 {
+            return self.read_temperature();
+        }
+
+        let mut raw_read: [u8; 1] = [0; 1];
+
+        match self.write_read_bytes(&[Register::TEMPERATURE], &mut raw_read) {
+            Ok(()) => match self.read_count_remaining() {
+                Ok(ccounter) => match self.read_count_per_degree() {
+                    Ok(slope) => {
+                        //Seul le MSB signe est utilise, le demi-degre est ignore
+                        let mut temperature: f32 = raw_read[0] as i8 as f32;
+                        if slope != 0 {
+                            temperature -= 0.25;
+                            temperature += (slope as f32 - ccounter as f32) / slope as f32;
+                        }
+                        Ok(temperature)
+                    }
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Verifie qu'un capteur repond a l'adresse configuree en lisant son registre de
+    /// configuration. Permet de controler le cablage (alimentation, adresse A2..A0)
+    /// sans interpreter de temperature ; un NAK ou toute autre erreur de bus donne `false`.
+    pub fn ping(&mut self) -> bool {
+        self.read_config().is_ok()
+    }
+
+    /// Estime si le composant a l'adresse configuree se comporte comme le capteur
+    /// attendu, aucun registre d'identification n'existant. Sont verifies :
+    ///
+    /// - les bits 3 et 2 de la configuration, lus a 1 sur le DS1621 et egaux a la
+    ///   resolution programmee sur le DS1631 ;
+    /// - la temperature et les seuils TH/TL, dans la plage -55..=125°C et sans bits
+    ///   parasites sous la resolution.
+    ///
+    /// `Ok(false)` signale par exemple un autre composant I2C partageant l'adresse.
+    pub fn probe(&mut self) -> Result<bool, Error<E>> {
+        let reserved = self.read_config()? & (ConfigRegBits::R1 | ConfigRegBits::R0);
+        let expected = match self.variant {
+            Variant::Ds1621 => ConfigRegBits::R1 | ConfigRegBits::R0,
+            Variant::Ds1631 => self.resolution.config_bits(),
+        };
+        if reserved != expected {
+            return Ok(false);
+        }
+
+        let unused_bits = 0xFF >> self.resolution.fraction_bits();
+        for reg in [
+            Register::TEMPERATURE,
+            Register::ACCESS_TH,
+            Register::ACCESS_TL,
+        ] {
+            let raw_read = self.read_register_raw(reg)?;
+            let millicelsius = decode_millicelsius(raw_read[0], raw_read[1], self.resolution);
+
+            if raw_read[1] & unused_bits != 0
+                || !(TEMP_MIN_MC..=TEMP_MAX_MC).contains(&millicelsius)
+            {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Lit l'octet brut du registre de configuration, a decoder avec
+    /// [`Config::from_bits`] ou les masques du module [`raw`]
+    pub fn read_config(&mut self) -> Result<u8, Error<E>> {
+        let mut u8rd_buff: [u8; 1] = [0; 1];
+
+        match self.write_read_bytes(&[Register::ACCESS_CONFIG], &mut u8rd_buff) {
+            Ok(()) => {
+                self.cached_config = Some(u8rd_buff[0]);
+                if u8rd_buff[0] & ConfigRegBits::NVB == 0 {
+                    self.nv_pending = false;
+                }
+                Ok(u8rd_buff[0])
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Attend la fin de la conversion en cours en scrutant le bit DONE, `delay` etant
+    /// appele entre deux lectures du registre de configuration.
+    /// Renvoie [`Error::Timeout`] si DONE n'est pas apparu apres le nombre de lectures
+    /// fixe par [`set_conversion_timeout`](Self::set_conversion_timeout), ce qui evite
+    /// de boucler indefiniment sur un capteur bloque.
+    pub fn wait_for_conversion<F>(&mut self, mut delay: F) -> Result<(), Error<E>>
+    where
+        F: FnMut(),
+    {
+        for poll in 0..self.max_conversion_polls {
+            if poll != 0 {
+                delay();
+            }
+
+            match self.read_config() {
+                Ok(conf_val) if conf_val & ConfigRegBits::DONE != 0 => return Ok(()),
+                Ok(_) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    #[cfg(feature = "float")]
+    //Comme wait_for_conversion juste apres un Start Convert : DONE reste leve par la
+    //conversion precedente tant que le capteur n'a pas demarre la nouvelle, il n'est
+    //donc retenu qu'a partir de la deuxieme lecture, apres un premier `delay`. Deux
+    //lectures au moins sont faites, quel que soit set_conversion_timeout
+    fn wait_for_fresh_conversion<F>(&mut self, mut delay: F) -> Result<(), Error<E>>
+    where
+        F: FnMut(),
+    {
+        for poll in 0..self.max_conversion_polls.max(2) {
+            if poll != 0 {
+                delay();
+            }
+
+            match self.read_config() {
+                Ok(conf_val) if poll != 0 && conf_val & ConfigRegBits::DONE != 0 => return Ok(()),
+                Ok(_) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    #[cfg(feature = "float")]
+    /// Equivalent non bloquant de [`wait_for_conversion`](Self::wait_for_conversion)
+    /// suivi de [`read_temperature`](Self::read_temperature), pour une machine a etats :
+    /// lit une fois la configuration et, si DONE est leve, la temperature.
+    ///
+    /// Juste apres [`start_convert`](Self::start_convert), DONE peut encore etre celui
+    /// de la conversion precedente : le premier appel suivant Start Convert doit etre
+    /// differe au prochain passage dans la boucle, ou son resultat ignore.
+    ///
+    /// ```ignore
+    /// sensor.start_convert()?;
+    /// // ... a chaque passage suivant dans la boucle principale :
+    /// if let Conversion::Done(t) = sensor.poll_conversion()? { ... }
+    /// ```
+    pub fn poll_conversion(&mut self) -> Result<Conversion, Error<E>> {
+        match self.read_config() {
+            Ok(conf_val) if conf_val & ConfigRegBits::DONE != 0 => match self.read_temperature() {
+                Ok(temp) => Ok(Conversion::Done(temp)),
+                Err(e) => Err(e),
+            },
+            Ok(_) => Ok(Conversion::InProgress),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit le mode de conversion reellement programme dans le capteur (bit 1SHOT).
+    /// Contrairement au mode memorise par le driver, cette valeur reste juste si le
+    /// registre de configuration a ete modifie par ailleurs (autre code, baisse de tension).
+    pub fn get_convert_mode(&mut self) -> Result<Mode, Error<E>> {
+        match self.read_config() {
+            Ok(conf_val) => Ok(Mode::from_config(conf_val)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Renvoie `true` si la sortie thermostat Tout est active a l'etat haut (bit POL)
+    pub fn get_polarity(&mut self) -> Result<bool, Error<E>> {
+        match self.read_config() {
+            Ok(conf_val) => Ok(conf_val & ConfigRegBits::POL != 0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Renvoie `true` tant qu'une ecriture en memoire non volatile (TH, TL ou
+    /// configuration) est en cours (bit NVB). Une nouvelle ecriture lancee pendant
+    /// ce temps peut corrompre la valeur en cours d'enregistrement.
+    pub fn is_nv_busy(&mut self) -> Result<bool, Error<E>> {
+        match self.read_config() {
+            Ok(conf_val) => Ok(conf_val & ConfigRegBits::NVB != 0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Attend que le bit NVB retombe, `delay` etant appele entre deux lectures.
+    /// D'apres la datasheet une ecriture en EEPROM dure au plus 10ms ; au dela de
+    /// 100 lectures la methode renvoie [`Error::Timeout`].
+    pub fn wait_for_nv_write<F>(&mut self, delay: F) -> Result<(), Error<E>>
+    where
+        F: FnMut(),
+    {
+        self.poll_nv_write(NV_WRITE_MAX_POLLS, delay)
+    }
+
+    fn poll_nv_write<F>(&mut self, max_polls: u32, mut delay: F) -> Result<(), Error<E>>
+    where
+        F: FnMut(),
+    {
+        for poll in 0..max_polls {
+            if poll != 0 {
+                delay();
+            }
+
+            match self.is_nv_busy() {
+                Ok(false) => return Ok(()),
+                Ok(true) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Lit le registre de configuration et le decode bit a bit
+    pub fn read_config_struct(&mut self) -> Result<Config, Error<E>> {
+        match self.read_config() {
+            Ok(conf_val) => Ok(Config::from_bits(conf_val)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit en un appel la configuration et les deux seuils, l'etat a relever lors d'un
+    /// diagnostic
+    pub fn dump(&mut self) -> Result<DeviceState, Error<E>> {
+        let conf_val = self.read_config()?;
+        let high = self.read_register_raw(Register::ACCESS_TH)?;
+        let low = self.read_register_raw(Register::ACCESS_TL)?;
+
+        Ok(DeviceState {
+            mode: Mode::from_config(conf_val),
+            active_high: conf_val & ConfigRegBits::POL != 0,
+            temp_high_flag: conf_val & ConfigRegBits::THF != 0,
+            temp_low_flag: conf_val & ConfigRegBits::TLF != 0,
+            nv_busy: conf_val & ConfigRegBits::NVB != 0,
+            high: Temperature(decode_raw(high[0], high[1], Resolution::Bits9)),
+            low: Temperature(decode_raw(low[0], low[1], Resolution::Bits9)),
+        })
+    }
+
+    #[cfg(feature = "float")]
+    /// Lit la temperature puis les indicateurs verrouilles, renvoyes sous la forme
+    /// `(temperature, THF, TLF)`. Il s'agit de deux transactions successives : une
+    /// conversion peut se terminer entre les deux et lever un indicateur que la
+    /// temperature renvoyee ne justifie pas encore.
+    pub fn read_temperature_with_flags(&mut self) -> Result<(f32, bool, bool), Error<E>> {
+        let temp = self.read_temperature()?;
+
+        match self.read_status() {
+            Ok(status) => Ok((temp, status.temp_high_flag, status.temp_low_flag)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Ecrit dans `w` un rapport lisible de l'etat du capteur ([`dump`](Self::dump))
+    /// et de la temperature courante, sans allocation, par exemple vers une UART :
+    ///
+    /// ```text
+    /// mode: continuous
+    /// Tout: active low
+    /// TH: 30.0°C
+    /// TL: 25.0°C
+    /// THF: 0 TLF: 0 NVB: 0
+    /// temperature: 25.5°C
+    /// ```
+    pub fn write_report<W>(&mut self, w: &mut W) -> Result<(), Error<E>>
+    where
+        W: core::fmt::Write,
+    {
+        let state = self.dump()?;
+        let temp = Temperature(self.read_temperature_raw()?);
+
+        match write_report_lines(w, &state, temp) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(Error::Format),
+        }
+    }
+
+    /// Lit le registre de configuration et decode les indicateurs d'etat
+    pub fn read_status(&mut self) -> Result<Status, Error<E>> {
+        match self.read_config() {
+            Ok(conf_val) => Ok(Status::from_config(conf_val)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit le compteur COUNT_REMAIN (commande 0xA8) de la derniere conversion, pour
+    /// une calibration ou un calcul haute resolution fait par l'application.
+    /// Absent sur les DS1631/DS1721.
+    pub fn read_count_remaining(&mut self) -> Result<u8, Error<E>> {
+        let mut u8rd_buff: [u8; 1] = [0; 1];
+
+        match self.write_read_bytes(&[Register::READ_COUNTER], &mut u8rd_buff) {
+            Ok(()) => Ok(u8rd_buff[0]),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lit le nombre de coups par degre COUNT_PER_C (commande 0xA9) de la derniere
+    /// conversion. Absent sur les DS1631/DS1721.
+    pub fn read_count_per_degree(&mut self) -> Result<u8, Error<E>> {
+        let mut u8rd_buff: [u8; 1] = [0; 1];
+
+        match self.write_read_bytes(&[Register::READ_SLOPE], &mut u8rd_buff) {
+            Ok(()) => Ok(u8rd_buff[0]),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[deprecated(note = "renamed to read_count_remaining")]
+    pub fn read_counter(&mut self) -> Result<u8, Error<E>> {
+        self.read_count_remaining()
+    }
+
+    #[deprecated(note = "renamed to read_count_per_degree")]
+    pub fn read_slope(&mut self) -> Result<u8, Error<E>> {
+        self.read_count_per_degree()
+    }
+}
+
+/// Iterateur renvoye par [`Ds1621::readings`]
+#[cfg(feature = "float")]
+#[derive(Debug)]
+pub struct Readings<'a, I2C> {
+    sensor: &'a mut Ds1621<I2C>,
+}
+
+#[cfg(feature = "float")]
+impl<I2C, E> Iterator for Readings<'_, I2C>
+where
+    I2C: WriteRead<Error = E>,
+{
+    type Item = Result<f32, Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.sensor.read_temperature())
+    }
+}
+
+/// Capteur de temperature generique, pour ecrire du code applicatif testable avec
+/// un capteur simule a la place de [`Ds1621`]
+#[cfg(feature = "float")]
+pub trait TemperatureSensor {
+    type Error;
+
+    /// Temperature en degres Celsius
+    fn read_temperature(&mut self) -> Result<f32, Self::Error>;
+}
+
+#[cfg(feature = "float")]
+impl<I2C, E> TemperatureSensor for Ds1621<I2C>
+where
+    I2C: WriteRead<Error = E>,
+{
+    type Error = Error<E>;
+
+    fn read_temperature(&mut self) -> Result<f32, Error<E>> {
+        Ds1621::read_temperature(self)
+    }
+}
+
+/// Sens d'evolution de la temperature renvoye par [`TrendTracker::update`]
+#[cfg(feature = "float")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Trend {
+    /// Hausse superieure a la bande morte
+    Rising,
+    /// Baisse superieure a la bande morte
+    Falling,
+    /// Variation contenue dans la bande morte
+    Stable,
+}
+
+/// Detection de tendance entre lectures successives, par exemple celles de
+/// [`Ds1621::read_temperature`] : l'echantillonnage reste dans le code applicatif.
+///
+/// La reference n'est deplacee que lorsqu'une variation depasse la bande morte, si bien
+/// qu'une derive lente finit par etre signalee et que le bruit autour d'une consigne
+/// reste [`Trend::Stable`].
+#[cfg(feature = "float")]
+#[derive(Debug, Copy, Clone)]
+pub struct TrendTracker {
+    last: Option<f32>,
+    dead_band: f32,
+}
+
+#[cfg(feature = "float")]
+impl TrendTracker {
+    /// Cree un suivi sans reference, avec une bande morte en degres Celsius
+    pub fn new(dead_band: f32) -> Self {
+        TrendTracker {
+            last: None,
+            dead_band,
+        }
+    }
+
+    /// Compare `t` a la reference ; la premiere lecture sert de reference et renvoie
+    /// [`Trend::Stable`]
+    pub fn update(&mut self, t: f32) -> Trend {
+        let last = match self.last {
+            Some(last) => last,
+            None => {
+                self.last = Some(t);
+                return Trend::Stable;
+            }
+        };
+
+        let trend = if t - last > self.dead_band {
+            Trend::Rising
+        } else if last - t > self.dead_band {
+            Trend::Falling
+        } else {
+            Trend::Stable
+        };
+        if trend != Trend::Stable {
+            self.last = Some(t);
+        }
+        trend
+    }
+
+    /// Oublie la reference, la prochaine lecture repartira de zero
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Moyenne glissante des `N` dernieres mesures en demi-degres, pour lisser a
+/// l'affichage le scintillement de 0.5°C du mode continu, sans allocation
+#[cfg(feature = "float")]
+#[derive(Debug)]
+pub struct Smoothed<I2C, const N: usize> {
+    sensor: Ds1621<I2C>,
+    samples: [i16; N],
+    len: usize,
+    next: usize,
+}
+
+#[cfg(feature = "float")]
+impl<I2C, const N: usize> Smoothed<I2C, N> {
+    pub fn new(sensor: Ds1621<I2C>) -> Self {
+        Smoothed {
+            sensor,
+            samples: [0; N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Vide l'historique, par exemple apres un changement de mode
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// Rend le driver
+    pub fn release(self) -> Ds1621<I2C> {
+        self.sensor
+    }
+}
+
+#[cfg(feature = "float")]
+impl<I2C, E, const N: usize> Smoothed<I2C, N>
+where
+    I2C: WriteRead<Error = E>,
+{
+    /// Prend une nouvelle mesure et renvoie la moyenne des `N` dernieres (ou de toutes
+    /// celles deja prises tant que l'historique n'est pas plein), en degres Celsius
+    pub fn read(&mut self) -> Result<f32, Error<E>> {
+        let sample = self.sensor.read_temperature_raw()?;
+        if N == 0 {
+            return Ok(sample as f32 / 2.0);
+        }
+
+        self.samples[self.next] = sample;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+
+        let sum: i32 = self.samples[..self.len].iter().map(|&s| s as i32).sum();
+        Ok(sum as f32 / (2 * self.len) as f32)
+    }
+}
+
+/// Configuration initiale du capteur (adresse, mode, polarite) appliquee par une
+/// seule ecriture du registre de configuration lors de [`build`](Self::build)
+#[derive(Debug, Copy, Clone)]
+pub struct Ds1621Builder {
+    addr: Address,
+    mode: Mode,
+    active_high: bool,
+    retries: u8,
+}
+
+impl Default for Ds1621Builder {
+    fn default() -> Self {
+        Ds1621Builder {
+            addr: Address::default(),
+            mode: Mode::Continuous,
+            active_high: false,
+            retries: 0,
+        }
+    }
+}
+
+impl Ds1621Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adresse I2C du capteur, [`Address::PINS_000`] (0x48) par defaut
+    pub fn address(mut self, a_addr: Address) -> Self {
+        self.addr = a_addr;
+        self
+    }
+
+    /// Mode de conversion, continu par defaut
+    pub fn mode(mut self, a_mode: Mode) -> Self {
+        self.mode = a_mode;
+        self
+    }
+
+    /// Polarite de la sortie Tout, active a l'etat bas par defaut
+    pub fn polarity(mut self, active_high: bool) -> Self {
+        self.active_high = active_high;
+        self
+    }
+
+    /// Nouvelles tentatives sur erreur I2C, voir [`Ds1621::set_retries`]
+    pub fn retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Cree le driver et ecrit la configuration en une seule transaction.
+    /// Les indicateurs THF et TLF sont remis a zero par la meme occasion.
+    pub fn build<I2C, E>(self, i2c: I2C) -> Result<Ds1621<I2C>, Error<E>>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+    {
+        let mut sensor = Ds1621::new(i2c, self.addr);
+        sensor.retries = self.retries;
+
+        sensor.write_config(config_with_polarity(self.mode.apply(0), self.active_high))?;
+        sensor.mode = self.mode;
+        Ok(sensor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
     use super::*;
+    use std::vec::Vec;
+
+    /// Bus I2C simule : enregistre les trames ecrites et renvoie les reponses
+    /// preparees, dans l'ordre, a chaque lecture.
+    /// Avec `nak`, chaque transaction echoue comme si aucun capteur ne repondait ;
+    /// `failures` fait echouer les transactions suivantes, une fois chacune.
+    #[derive(Debug, Default, Clone)]
+    struct I2cMock {
+        writes: Vec<Vec<u8>>,
+        reads: Vec<Vec<u8>>,
+        nak: bool,
+        failures: u32,
+    }
+
+    impl I2cMock {
+        fn with_reads(reads: &[&[u8]]) -> Self {
+            I2cMock {
+                writes: Vec::new(),
+                reads: reads.iter().rev().map(|r| r.to_vec()).collect(),
+                ..Self::default()
+            }
+        }
+
+        fn absent() -> Self {
+            I2cMock {
+                nak: true,
+                ..Self::default()
+            }
+        }
+
+        fn fails(&mut self) -> bool {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return true;
+            }
+            self.nak
+        }
+
+        fn next_read(&mut self, buffer: &mut [u8]) {
+            let data = self.reads.pop().expect("unexpected read");
+            buffer.copy_from_slice(&data);
+        }
+    }
+
+    impl Write for I2cMock {
+        type Error = ();
+
+        fn write(&mut self, _addr: u8, bytes: &[u8]) -> Result<(), ()> {
+            if self.fails() {
+                return Err(());
+            }
+            self.writes.push(bytes.to_vec());
+            Ok(())
+        }
+    }
+
+    impl WriteRead for I2cMock {
+        type Error = ();
+
+        fn write_read(&mut self, _addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), ()> {
+            if self.fails() {
+                return Err(());
+            }
+            self.writes.push(bytes.to_vec());
+            self.next_read(buffer);
+            Ok(())
+        }
+    }
+
+    /// Bus absent dont chaque trait I2C a son propre type d'erreur
+    struct SplitErrorBus;
+
+    #[derive(Debug, PartialEq)]
+    struct WriteNak;
+
+    #[derive(Debug, PartialEq)]
+    struct ReadNak;
+
+    #[derive(Debug, PartialEq)]
+    enum BusError {
+        Write,
+        Read,
+    }
+
+    impl From<WriteNak> for BusError {
+        fn from(_: WriteNak) -> Self {
+            BusError::Write
+        }
+    }
+
+    impl From<ReadNak> for BusError {
+        fn from(_: ReadNak) -> Self {
+            BusError::Read
+        }
+    }
+
+    impl Write for SplitErrorBus {
+        type Error = WriteNak;
+
+        fn write(&mut self, _addr: u8, _bytes: &[u8]) -> Result<(), WriteNak> {
+            Err(WriteNak)
+        }
+    }
+
+    impl WriteRead for SplitErrorBus {
+        type Error = ReadNak;
+
+        fn write_read(
+            &mut self,
+            _addr: u8,
+            _bytes: &[u8],
+            _buffer: &mut [u8],
+        ) -> Result<(), ReadNak> {
+            Err(ReadNak)
+        }
+    }
+
+    /// Bus ou seuls les capteurs aux adresses `present` repondent
+    struct ScanBus {
+        present: &'static [u8],
+        probed: Vec<u8>,
+    }
+
+    impl WriteRead for ScanBus {
+        type Error = ();
+
+        fn write_read(&mut self, addr: u8, bytes: &[u8], _buffer: &mut [u8]) -> Result<(), ()> {
+            assert_eq!(bytes, [Register::ACCESS_CONFIG]);
+            self.probed.push(addr);
+            if self.present.contains(&addr) {
+                Ok(())
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    /// Temporisation simulee : cumule la duree demandee
+    #[cfg(feature = "float")]
+    #[derive(Debug, Default)]
+    struct DelayMock {
+        elapsed_ms: u32,
+    }
+
+    #[cfg(feature = "float")]
+    impl DelayMs<u16> for DelayMock {
+        fn delay_ms(&mut self, ms: u16) {
+            self.elapsed_ms += ms as u32;
+        }
+    }
+
+    #[cfg(feature = "float")]
+    impl DelayMs<u32> for DelayMock {
+        fn delay_ms(&mut self, ms: u32) {
+            self.elapsed_ms += ms;
+        }
+    }
+
+    #[test]
+    fn it_works() {}
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn temperature_codec_table() {
+        let table: [([u8; 2], f32, i32); 6] = [
+            ([0x00, 0x00], 0.0, 0),
+            ([0x00, 0x80], 0.5, 500),
+            ([0x7D, 0x00], 125.0, 125_000),
+            ([0xFF, 0x80], -0.5, -500),
+            ([0xC9, 0x00], -55.0, -55_000),
+            ([0xE6, 0x80], -25.5, -25_500),
+        ];
+
+        for (bytes, temp, millicelsius) in table {
+            assert_eq!(decode_temp(bytes[0], bytes[1], Resolution::Bits9), temp);
+            assert_eq!(
+                decode_millicelsius(bytes[0], bytes[1], Resolution::Bits9),
+                millicelsius
+            );
+            assert_eq!(encode_temp(temp), Some(bytes));
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_negative_temperature() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0xF5, 0x80]]));
+
+        assert_eq!(sensor.read_temperature().unwrap(), -10.5);
+        assert_eq!(sensor.i2c.writes, [[Register::TEMPERATURE]]);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_thresholds_back() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x1E, 0x00], &[0xFA, 0x80]]));
+
+        assert_eq!(sensor.read_high_temperature().unwrap(), 30.0);
+        assert_eq!(sensor.read_low_temperature().unwrap(), -5.5);
+        assert_eq!(
+            sensor.i2c.writes,
+            [[Register::ACCESS_TH], [Register::ACCESS_TL]]
+        );
+    }
+
+    #[test]
+    fn read_status_flags() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0b1101_0001]]));
+
+        assert_eq!(
+            sensor.read_status().unwrap(),
+            Status {
+                conversion_done: true,
+                temp_high_flag: true,
+                temp_low_flag: false,
+                nv_busy: true,
+            }
+        );
+        assert_eq!(sensor.i2c.writes, [[Register::ACCESS_CONFIG]]);
+    }
+
+    #[test]
+    fn config_struct_bits() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0b1010_0011]]));
+        let config = sensor.read_config_struct().unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                conversion_done: true,
+                temp_high_flag: false,
+                temp_low_flag: true,
+                nv_busy: false,
+                active_high: true,
+                one_shot: true,
+            }
+        );
+        assert_eq!(config.to_bits(), 0b1010_0011);
+        assert_eq!(config.mode(), Mode::OneShot);
+        for bits in 0..=u8::MAX {
+            let masked = bits & !(ConfigRegBits::RESERVED0 | ConfigRegBits::RESERVED1);
+            assert_eq!(Config::from_bits(bits).to_bits(), masked);
+        }
+    }
+
+    #[test]
+    fn write_config_verified_checks_readback() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x93], &[0x03], &[0x01]]));
+
+        assert!(sensor.write_config_verified(0x03).is_ok());
+        assert_eq!(sensor.write_config_verified(0x03), Err(Error::Verify));
+        assert_eq!(sensor.i2c.writes[0], [Register::ACCESS_CONFIG, 0x03]);
+        assert_eq!(sensor.i2c.writes[1], [Register::ACCESS_CONFIG]);
+    }
+
+    #[test]
+    fn ensure_config_writes_only_on_difference() {
+        let desired = Config::from_bits(ConfigRegBits::POL | ConfigRegBits::ONE_SHOT);
+
+        //Deja programme (indicateurs leves) : aucune ecriture
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0b1100_0011]]));
+        assert_eq!(sensor.ensure_config(desired), Ok(false));
+        assert_eq!(sensor.i2c.writes, [std::vec![Register::ACCESS_CONFIG]]);
+        assert_eq!(sensor.mode, Mode::OneShot);
+
+        //POL a programmer : seuls POL et 1SHOT changent
+        let mut sensor =
+            Ds1621::new_ds1631(I2cMock::with_reads(&[&[0b1100_1100]]), Address::PINS_000);
+        assert_eq!(sensor.ensure_config(desired), Ok(true));
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0b1100_1111]
+            ]
+        );
+        assert_eq!(sensor.mode, Mode::OneShot);
+        assert!(!sensor.needs_settle);
+
+        //Passage en mode continu : la premiere lecture attend une conversion
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x01]]));
+        assert_eq!(sensor.ensure_config(Config::from_bits(0x00)), Ok(true));
+        assert_eq!(sensor.i2c.writes[1], [Register::ACCESS_CONFIG, 0x00]);
+        assert_eq!(sensor.mode, Mode::Continuous);
+        assert!(sensor.needs_settle);
+    }
+
+    #[test]
+    fn clear_flags_preserves_other_bits() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0b0110_0001]]));
+
+        assert!(sensor.clear_flags().is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0b0000_0001]
+            ]
+        );
+    }
+
+    #[test]
+    fn check_crossings_reports_each_flag_once() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0b0110_0001],
+            &[0b0010_0001],
+            &[0b0000_0001],
+        ]));
+
+        assert_eq!(sensor.check_crossings(), Ok(Some(Crossing::High)));
+        assert_eq!(sensor.check_crossings(), Ok(Some(Crossing::Low)));
+        assert_eq!(sensor.check_crossings(), Ok(None));
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0b0010_0001],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0b0000_0001],
+                std::vec![Register::ACCESS_CONFIG],
+            ]
+        );
+    }
+
+    #[test]
+    fn shutdown_and_wake_follow_mode() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        assert!(sensor.shutdown().is_ok());
+        assert!(sensor.wake().is_ok());
+        assert!(sensor.needs_settle);
+        assert_eq!(
+            sensor.i2c.writes,
+            [[Register::STOP_CONVERT], [Register::START_CONVERT]]
+        );
+
+        sensor.mode = Mode::OneShot;
+        sensor.i2c.writes.clear();
+        assert!(sensor.wake().is_ok());
+        assert!(sensor.i2c.writes.is_empty());
+    }
+
+    #[test]
+    fn public_registers_match_datasheet() {
+        assert_eq!(
+            [
+                registers::TEMPERATURE,
+                registers::ACCESS_TH,
+                registers::ACCESS_TL,
+                registers::ACCESS_CONFIG,
+                registers::START_CONVERT,
+                registers::START_CONVERT_DS1631,
+                registers::STOP_CONVERT,
+                registers::READ_COUNTER,
+                registers::READ_SLOPE,
+            ],
+            [0xAA, 0xA1, 0xA2, 0xAC, 0xEE, 0x51, 0x22, 0xA8, 0xA9]
+        );
+    }
+
+    #[test]
+    fn clone_keeps_address_and_mode() {
+        let mut sensor = Ds1621::new(I2cMock::with_reads(&[&[0x8C]]), Address::PINS_011);
+        sensor.set_convert_mode(Mode::OneShot).unwrap();
+
+        let copy = sensor.clone();
+        assert_eq!(copy.addr, 0x4B);
+        assert_eq!(copy.mode, Mode::OneShot);
+        assert_eq!(sensor.cached_config, Some(0x8D));
+        assert_eq!(copy.cached_config, None);
+    }
+
+    #[test]
+    fn scan_lists_responding_addresses() {
+        let mut bus = ScanBus {
+            present: &[0x49, 0x4C, 0x4F],
+            probed: Vec::new(),
+        };
+        let mut found = [Address::default(); 8];
+
+        assert_eq!(scan(&mut bus, &mut found), 3);
+        assert_eq!(
+            found[..3],
+            [Address::PINS_001, Address::PINS_100, Address::PINS_111]
+        );
+        assert_eq!(bus.probed, (0x48..=0x4F).collect::<Vec<u8>>());
+
+        //Tampon trop petit : arret des qu'il est plein
+        let mut first = [Address::default(); 1];
+        bus.probed.clear();
+        assert_eq!(scan(&mut bus, &mut first), 1);
+        assert_eq!(first, [Address::PINS_001]);
+        assert_eq!(bus.probed, [0x48, 0x49]);
+    }
+
+    #[test]
+    fn dump_reads_config_and_thresholds() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0b1100_1011],
+            &[0x1E, 0x80],
+            &[0xFB, 0x00],
+        ]));
+
+        assert_eq!(
+            sensor.dump().unwrap(),
+            DeviceState {
+                mode: Mode::OneShot,
+                active_high: true,
+                temp_high_flag: true,
+                temp_low_flag: false,
+                nv_busy: false,
+                high: Temperature::from_half_degrees(61),
+                low: Temperature::from_half_degrees(-10),
+            }
+        );
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                [Register::ACCESS_CONFIG],
+                [Register::ACCESS_TH],
+                [Register::ACCESS_TL]
+            ]
+        );
+    }
+
+    #[test]
+    fn inner_mut_borrows_bus() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x00], &[0x19, 0x00]]));
+
+        let mut other = [0; 1];
+        sensor
+            .inner_mut()
+            .write_read(0x50, &[0x00], &mut other)
+            .unwrap();
+        assert_eq!(sensor.read_temperature_raw().unwrap(), 50);
+        assert_eq!(sensor.i2c.writes, [[0x00], [Register::TEMPERATURE]]);
+    }
+
+    #[test]
+    fn is_below_freezing_reads_sign_only() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0xFF], &[0x00], &[0x19]]));
+
+        assert!(sensor.is_below_freezing().unwrap());
+        assert!(!sensor.is_below_freezing().unwrap());
+        assert!(!sensor.is_below_freezing().unwrap());
+        assert_eq!(sensor.i2c.writes, [[Register::TEMPERATURE]; 3]);
+    }
+
+    #[test]
+    fn read_temperature_q8_8() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x80],
+            &[0xF5, 0x80],
+            &[0x19, 0xFF],
+        ]));
+
+        assert_eq!(sensor.read_temperature_q8_8().unwrap(), 0x1980);
+        assert_eq!(sensor.read_temperature_q8_8().unwrap(), 0xF580_u16 as i16);
+        //Bits parasites sous le demi-degre ignores
+        assert_eq!(sensor.read_temperature_q8_8().unwrap(), 0x1980);
+    }
+
+    #[test]
+    fn new_read_takes_mode_from_chip() {
+        let sensor = Ds1621::new_read(I2cMock::with_reads(&[&[0x8D]]), Address::PINS_000).unwrap();
+        assert_eq!(sensor.mode, Mode::OneShot);
+        assert_eq!(sensor.cached_config, Some(0x8D));
+
+        let sensor = Ds1621::new_read(I2cMock::with_reads(&[&[0x8C]]), Address::PINS_000).unwrap();
+        assert_eq!(sensor.mode, Mode::Continuous);
+
+        assert_eq!(
+            Ds1621::new_read(I2cMock::absent(), Address::PINS_000).err(),
+            Some(Error::I2C(()))
+        );
+    }
+
+    #[test]
+    fn retries_transient_bus_errors() {
+        let mut sensor = Ds1621::new_default(I2cMock {
+            failures: 1,
+            ..I2cMock::with_reads(&[&[0x19, 0x80]])
+        });
+
+        //Sans nouvelle tentative la premiere erreur est renvoyee
+        assert_eq!(sensor.read_temperature_raw(), Err(Error::I2C(())));
+
+        sensor.i2c.failures = 1;
+        sensor.set_retries(1);
+        assert_eq!(sensor.read_temperature_raw().unwrap(), 51);
+
+        sensor.i2c.failures = 2;
+        assert_eq!(sensor.start_convert(), Err(Error::I2C(())));
+        sensor.i2c.failures = 1;
+        assert!(sensor.start_convert().is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [[Register::TEMPERATURE], [Register::START_CONVERT]]
+        );
+
+        let sensor = Ds1621Builder::new()
+            .retries(3)
+            .build(I2cMock::default())
+            .unwrap();
+        assert_eq!(sensor.retries, 3);
+    }
+
+    #[test]
+    fn write_report_formats_state() {
+        use std::string::String;
+
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0b1100_1100],
+            &[0x1E, 0x00],
+            &[0x19, 0x00],
+            &[0x19, 0x80],
+        ]));
+        let mut report = String::new();
+
+        assert!(sensor.write_report(&mut report).is_ok());
+        assert_eq!(
+            report,
+            "mode: continuous\nTout: active low\nTH: 30.0°C\nTL: 25.0°C\n\
+             THF: 1 TLF: 0 NVB: 0\ntemperature: 25.5°C\n"
+        );
+    }
+
+    #[test]
+    fn consecutive_errors_reset_on_success() {
+        let mut sensor = Ds1621::new_default(I2cMock {
+            failures: 3,
+            ..I2cMock::with_reads(&[&[0x00]])
+        });
+        sensor.set_retries(1);
+
+        assert!(sensor.start_convert().is_err());
+        assert_eq!(sensor.consecutive_errors(), 2);
+        assert!(sensor.read_config().is_ok());
+        assert_eq!(sensor.consecutive_errors(), 0);
+    }
+
+    #[test]
+    fn read_count_registers() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[10], &[16]]));
+
+        assert_eq!(sensor.read_count_remaining().unwrap(), 10);
+        assert_eq!(sensor.read_count_per_degree().unwrap(), 16);
+        assert_eq!(sensor.i2c.writes, [[0xA8], [0xA9]]);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn high_res_on_ds1631_uses_programmed_resolution() {
+        let mut ds1631 =
+            Ds1621::new_ds1631(I2cMock::with_reads(&[&[0x19, 0xF0]]), Address::PINS_000);
+
+        //Resolution 12 bits par defaut, sans lecture des compteurs absents
+        assert_eq!(ds1631.read_temperature_high_res(), Ok(25.9375));
+        assert_eq!(ds1631.i2c.writes, [[Register::TEMPERATURE]]);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_high_res_temperature() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19],
+            &[10],
+            &[16],
+            &[0xF5],
+            &[4],
+            &[16],
+        ]));
+
+        assert_eq!(sensor.read_temperature_high_res().unwrap(), 25.125);
+        assert_eq!(sensor.read_temperature_high_res().unwrap(), -10.5);
+        assert_eq!(
+            sensor.i2c.writes[..3],
+            [
+                [Register::TEMPERATURE],
+                [Register::READ_COUNTER],
+                [Register::READ_SLOPE]
+            ]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn one_shot_waits_for_done() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x01],
+            &[0x01],
+            &[0x81],
+            &[0x19, 0x80],
+        ]));
+        let mut delays = 0;
+
+        assert_eq!(
+            sensor.read_temperature_one_shot(|| delays += 1).unwrap(),
+            25.5
+        );
+        assert_eq!(delays, 2);
+        assert_eq!(sensor.i2c.writes[0], [Register::START_CONVERT]);
+        assert_eq!(sensor.i2c.writes[4], [Register::TEMPERATURE]);
+    }
+
+    #[test]
+    fn wait_for_conversion_times_out() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x01], &[0x01], &[0x01]]));
+
+        sensor.set_conversion_timeout(3);
+
+        assert_eq!(sensor.wait_for_conversion(|| {}), Err(Error::Timeout));
+        assert_eq!(sensor.i2c.writes.len(), 3);
+    }
+
+    #[test]
+    fn mode_config_bit_round_trip() {
+        for bits in 0..=u8::MAX {
+            let expected = if bits & raw::ONE_SHOT != 0 {
+                Mode::OneShot
+            } else {
+                Mode::Continuous
+            };
+            assert_eq!(Mode::from_config(bits), expected);
+
+            for mode in [Mode::Continuous, Mode::OneShot] {
+                let applied = mode.apply(bits);
+                assert_eq!(Mode::from_config(applied), mode);
+                assert_eq!(applied & !raw::ONE_SHOT, bits & !raw::ONE_SHOT);
+            }
+        }
+    }
+
+    #[test]
+    fn mode_and_polarity_are_independent() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x01], &[0x03], &[0x02]]));
+
+        assert!(sensor.set_polarity(true).is_ok());
+        assert_eq!(sensor.i2c.writes[1], [Register::ACCESS_CONFIG, 0x03]);
+        assert!(sensor.set_convert_mode(Mode::Continuous).is_ok());
+        assert_eq!(sensor.i2c.writes[3], [Register::ACCESS_CONFIG, 0x02]);
+        assert!(sensor.set_convert_mode(Mode::OneShot).is_ok());
+        assert_eq!(sensor.i2c.writes[5], [Register::ACCESS_CONFIG, 0x03]);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn fahrenheit_and_kelvin() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x19, 0x00], &[0x19, 0x00]]));
+
+        assert!((sensor.read_temperature_fahrenheit().unwrap() - 77.0).abs() < 1e-3);
+        assert!((sensor.read_temperature_kelvin().unwrap() - 298.15).abs() < 1e-3);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_latest_follows_programmed_mode() {
+        let mut delay = DelayMock::default();
+
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x00], &[0x19, 0x00]]));
+        assert_eq!(sensor.read_latest(&mut delay).unwrap(), 25.0);
+        assert_eq!(
+            sensor.i2c.writes,
+            [[Register::ACCESS_CONFIG], [Register::TEMPERATURE]]
+        );
+
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x01],
+            &[0x81],
+            &[0x81],
+            &[0x19, 0x80],
+        ]));
+        assert_eq!(sensor.read_latest(&mut delay).unwrap(), 25.5);
+        assert_eq!(sensor.i2c.writes[1], [Register::START_CONVERT]);
+        assert_eq!(delay.elapsed_ms, 10);
+    }
+
+    /// Capteur en mode one shot : apres Start Convert, DONE reste leve par la
+    /// conversion precedente pour la premiere lecture de la configuration, puis
+    /// retombe pendant `busy_polls` lectures
+    #[cfg(feature = "float")]
+    struct OneShotChip {
+        started: bool,
+        stale_polls: u32,
+        busy_polls: u32,
+        writes: Vec<Vec<u8>>,
+    }
+
+    #[cfg(feature = "float")]
+    impl Write for OneShotChip {
+        type Error = ();
+
+        fn write(&mut self, _addr: u8, bytes: &[u8]) -> Result<(), ()> {
+            if bytes == [Register::START_CONVERT] {
+                self.started = true;
+                self.stale_polls = 1;
+                self.busy_polls = 2;
+            }
+            self.writes.push(bytes.to_vec());
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "float")]
+    impl WriteRead for OneShotChip {
+        type Error = ();
+
+        fn write_read(&mut self, _addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), ()> {
+            self.writes.push(bytes.to_vec());
+            match bytes[0] {
+                Register::ACCESS_CONFIG => {
+                    let done = if self.stale_polls > 0 {
+                        self.stale_polls -= 1;
+                        true
+                    } else if self.busy_polls > 0 {
+                        self.busy_polls -= 1;
+                        false
+                    } else {
+                        true
+                    };
+                    buffer[0] = if done { 0x81 } else { 0x01 };
+                }
+                _ => {
+                    //Temperature valide uniquement apres une conversion complete
+                    assert!(self.started && self.busy_polls == 0, "stale temperature");
+                    buffer.copy_from_slice(&[0x19, 0x80]);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn one_shot_reads_twice_with_single_poll_timeout() {
+        let mut sensor =
+            Ds1621::new_default(I2cMock::with_reads(&[&[0x81], &[0x81], &[0x19, 0x00]]));
+
+        sensor.set_conversion_timeout(1);
+        assert_eq!(sensor.read_temperature_one_shot(|| {}), Ok(25.0));
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn one_shot_ignores_stale_done() {
+        let mut sensor = Ds1621::new_default(OneShotChip {
+            started: false,
+            stale_polls: 0,
+            busy_polls: 0,
+            writes: Vec::new(),
+        });
+        let mut delays = 0;
+
+        assert_eq!(
+            sensor.read_temperature_one_shot(|| delays += 1).unwrap(),
+            25.5
+        );
+        assert_eq!(sensor.i2c.writes[0], [Register::START_CONVERT]);
+        //DONE perime, deux lectures en cours de conversion, DONE frais
+        assert_eq!(sensor.i2c.writes.len(), 6);
+        assert_eq!(delays, 3);
+    }
+
+    #[test]
+    fn ping_detects_absent_sensor() {
+        let mut present = Ds1621::new_default(I2cMock::with_reads(&[&[0x8C]]));
+        assert!(present.ping());
+        assert_eq!(present.i2c.writes, [[Register::ACCESS_CONFIG]]);
+
+        let mut absent = Ds1621::new(I2cMock::absent(), Address::PINS_111);
+        assert!(!absent.ping());
+    }
+
+    #[test]
+    fn bus_errors_are_wrapped() {
+        let mut sensor = Ds1621::new_default(I2cMock::absent());
+
+        assert_eq!(sensor.start_convert(), Err(Error::I2C(())));
+        assert_eq!(sensor.stop_convert(), Err(Error::I2C(())));
+        assert_eq!(sensor.read_config(), Err(Error::I2C(())));
+        assert_eq!(sensor.read_temperature_millicelsius(), Err(Error::I2C(())));
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn thermostat_state_against_thresholds() {
+        let table = [
+            (31.0, ThermostatState::Above),
+            (30.0, ThermostatState::Above),
+            (27.5, ThermostatState::Within),
+            (25.0, ThermostatState::Below),
+            (-3.0, ThermostatState::Below),
+        ];
+
+        for (t, state) in table {
+            //TH = 30°C, TL = 25°C
+            let mut sensor =
+                Ds1621::new_default(I2cMock::with_reads(&[&[0x1E, 0x00], &[0x19, 0x00]]));
+            assert_eq!(sensor.thermostat_state(t).unwrap(), state);
+            assert_eq!(
+                sensor.i2c.writes,
+                [[Register::ACCESS_TH], [Register::ACCESS_TL]]
+            );
+        }
+    }
+
+    #[test]
+    fn raw_temperature_in_half_degrees() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x80],
+            &[0xF5, 0x80],
+            &[0x00, 0x00],
+        ]));
+
+        assert_eq!(sensor.read_temperature_raw().unwrap(), 51);
+        assert_eq!(sensor.read_temperature_raw().unwrap(), -21);
+        assert_eq!(sensor.read_temperature_raw().unwrap(), 0);
+
+        let mut ds1631 =
+            Ds1621::new_ds1631(I2cMock::with_reads(&[&[0x19, 0xF0]]), Address::PINS_000);
+        assert_eq!(ds1631.read_temperature_raw().unwrap(), 51);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_both_thresholds() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x1E, 0x80], &[0xFB, 0x00]]));
+
+        assert_eq!(sensor.read_thresholds().unwrap(), (30.5, -5.0));
+        assert_eq!(
+            sensor.i2c.writes,
+            [[Register::ACCESS_TH], [Register::ACCESS_TL]]
+        );
+    }
+
+    #[test]
+    fn new_with_config_programs_chip() {
+        let config = Config {
+            active_high: true,
+            one_shot: true,
+            ..Config::default()
+        };
+        let sensor =
+            Ds1621::new_with_config(I2cMock::default(), Address::PINS_010, config).unwrap();
+
+        assert_eq!(sensor.addr, 0x4A);
+        assert_eq!(sensor.mode, Mode::OneShot);
+        assert_eq!(sensor.i2c.writes, [[Register::ACCESS_CONFIG, 0x03]]);
+    }
+
+    #[test]
+    fn temperature_display() {
+        use std::format;
+
+        let table = [
+            (51, "25.5"),
+            (50, "25.0"),
+            (0, "0.0"),
+            (-1, "-0.5"),
+            (-21, "-10.5"),
+            (-110, "-55.0"),
+            (250, "125.0"),
+        ];
+
+        for (halves, text) in table {
+            assert_eq!(format!("{}", Temperature::from_half_degrees(halves)), text);
+        }
+        assert_eq!(
+            format!("{:#}", Temperature::from_half_degrees(51)),
+            "25.5°C"
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn temperature_to_celsius() {
+        let t = Temperature::from_half_degrees(-21);
+        assert_eq!(t.celsius(), -10.5);
+        assert_eq!(f32::from(t), -10.5);
+    }
+
+    #[test]
+    fn stray_lsb_bits_ignored() {
+        //Seul le bit 7 du LSB compte en 9 bits : 0x7F ne doit pas ajouter 0.5°C
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x7F],
+            &[0x19, 0x7F],
+            &[0xF5, 0x7F],
+        ]));
+
+        assert_eq!(sensor.read_temperature_millicelsius().unwrap(), 25_000);
+        assert_eq!(sensor.read_temperature_raw().unwrap(), 50);
+        assert_eq!(sensor.read_temperature_millicelsius().unwrap(), -11_000);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn stray_lsb_bits_ignored_float() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x19, 0x7F], &[0x19, 0xFF]]));
+
+        assert_eq!(sensor.read_temperature().unwrap(), 25.0);
+        assert_eq!(sensor.read_temperature().unwrap(), 25.5);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn temperature_sensor_trait() {
+        struct FakeSensor(f32);
+
+        impl TemperatureSensor for FakeSensor {
+            type Error = ();
+
+            fn read_temperature(&mut self) -> Result<f32, ()> {
+                Ok(self.0)
+            }
+        }
+
+        fn too_hot<S: TemperatureSensor>(sensor: &mut S) -> bool {
+            matches!(sensor.read_temperature(), Ok(t) if t > 30.0)
+        }
+
+        assert!(too_hot(&mut FakeSensor(31.5)));
+        assert!(!too_hot(&mut Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00]
+        ]))));
+    }
+
+    #[test]
+    fn conversion_time_per_resolution() {
+        assert_eq!(
+            Ds1621::new_default(I2cMock::default()).max_conversion_time_ms(),
+            750
+        );
+
+        let mut ds1631 = Ds1621::new_ds1631(I2cMock::with_reads(&[&[0x0C]]), Address::PINS_000);
+        assert_eq!(ds1631.max_conversion_time_ms(), 750);
+        assert!(ds1631.set_resolution(Resolution::Bits9).is_ok());
+        assert_eq!(ds1631.max_conversion_time_ms(), 94);
+    }
 
     #[test]
-    fn it_works() {}
+    fn raw_masks_match_config_fields() {
+        let config = Config::from_bits(raw::DONE | raw::TLF | raw::POL);
+
+        assert!(config.conversion_done && config.temp_low_flag && config.active_high);
+        assert!(!config.temp_high_flag && !config.nv_busy && !config.one_shot);
+        assert_eq!(
+            Config::from_bits(raw::THF | raw::NVB | raw::ONE_SHOT).to_bits(),
+            0x51
+        );
+        assert_eq!(Resolution::Bits12.config_bits(), raw::R1 | raw::R0);
+    }
+
+    #[test]
+    fn start_continuous_kicks_off_conversions() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x01]]));
+        sensor.mode = Mode::OneShot;
+
+        assert!(sensor.start_continuous().is_ok());
+        assert_eq!(sensor.mode, Mode::Continuous);
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0x00],
+                std::vec![Register::START_CONVERT],
+            ]
+        );
+    }
+
+    #[test]
+    fn temperature_bytes_are_verbatim() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0xF5, 0x7F]]));
+        let mut buf = [0; 2];
+
+        assert!(sensor.read_temperature_bytes(&mut buf).is_ok());
+        assert_eq!(buf, [0xF5, 0x7F]);
+        assert_eq!(sensor.i2c.writes, [[Register::TEMPERATURE]]);
+    }
+
+    #[test]
+    fn config_write_waits_for_nv_write() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x10], &[0x10], &[0x00]]));
+
+        assert!(sensor.write_th_raw([0x1E, 0x00]).is_ok());
+        assert!(sensor.set_polarity(true).is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_TH, 0x1E, 0x00],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0x02],
+            ]
+        );
+
+        //Sans attente : NVB relu en boucle 1000 fois
+        let busy: Vec<&[u8]> = std::vec![&[0x10]; 1000];
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&busy));
+        assert!(sensor.write_tl_raw([0x19, 0x00]).is_ok());
+        assert_eq!(sensor.write_config(0x00), Err(Error::Timeout));
+        assert_eq!(sensor.i2c.writes.len(), 1001);
+    }
+
+    #[test]
+    fn config_write_nv_wait_uses_poll_delay() {
+        use core::sync::atomic::{AtomicU32, Ordering};
+
+        static DELAYS: AtomicU32 = AtomicU32::new(0);
+        fn delay() {
+            DELAYS.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x10], &[0x10], &[0x00]]));
+        sensor.set_nv_poll_delay(delay);
+        assert!(sensor.write_th_raw([0x1E, 0x00]).is_ok());
+        assert!(sensor.write_config(0x02).is_ok());
+        assert_eq!(DELAYS.load(Ordering::Relaxed), 2);
+
+        //Au plus 100 lectures espacees de `delay`
+        let busy: Vec<&[u8]> = std::vec![&[0x10]; 100];
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&busy));
+        sensor.set_nv_poll_delay(delay);
+        assert!(sensor.write_tl_raw([0x19, 0x00]).is_ok());
+        assert_eq!(sensor.write_config(0x00), Err(Error::Timeout));
+        assert_eq!(sensor.i2c.writes.len(), 101);
+        assert_eq!(DELAYS.load(Ordering::Relaxed), 2 + 99);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn settled_read_waits_once_after_entering_continuous() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00],
+            &[0x01],
+            &[0x19, 0x80],
+            &[0x1A, 0x00],
+        ]));
+        let mut delay = DelayMock::default();
+
+        assert_eq!(sensor.read_temperature_settled(&mut delay).unwrap(), 25.0);
+        assert_eq!(delay.elapsed_ms, 0);
+
+        assert!(sensor.set_convert_mode(Mode::Continuous).is_ok());
+        assert_eq!(sensor.read_temperature_settled(&mut delay).unwrap(), 25.5);
+        assert_eq!(delay.elapsed_ms, 750);
+        assert_eq!(sensor.read_temperature_settled(&mut delay).unwrap(), 26.0);
+        assert_eq!(delay.elapsed_ms, 750);
+
+        //Une lecture ordinaire suffit a lever l'attente
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x00],
+            &[0x19, 0x00],
+            &[0x19, 0x80],
+        ]));
+        let mut delay = DelayMock::default();
+        assert!(sensor.set_convert_mode(Mode::Continuous).is_ok());
+        assert_eq!(sensor.read_temperature(), Ok(25.0));
+        assert_eq!(sensor.read_temperature_settled(&mut delay).unwrap(), 25.5);
+        assert_eq!(delay.elapsed_ms, 0);
+    }
+
+    #[test]
+    fn probe_recognises_ds1621() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x8C],
+            &[0x19, 0x80],
+            &[0x4B, 0x00],
+            &[0x50, 0x00],
+        ]));
+        assert_eq!(sensor.probe(), Ok(true));
+        assert_eq!(sensor.i2c.writes.len(), 4);
+
+        //Reserves a 0 : autre composant
+        let mut other = Ds1621::new_default(I2cMock::with_reads(&[&[0x00]]));
+        assert_eq!(other.probe(), Ok(false));
+
+        //Bus flottant : 0xFF partout, bits parasites dans le LSB
+        let mut floating = Ds1621::new_default(I2cMock::with_reads(&[&[0xFF], &[0xFF, 0xFF]]));
+        assert_eq!(floating.probe(), Ok(false));
+
+        //Temperature hors plage (127°C)
+        let mut hot = Ds1621::new_default(I2cMock::with_reads(&[&[0x8C], &[0x7F, 0x00]]));
+        assert_eq!(hot.probe(), Ok(false));
+
+        let mut ds1631 = Ds1621::new_ds1631(
+            I2cMock::with_reads(&[&[0x0C], &[0x19, 0xF0], &[0x1E, 0x00], &[0x19, 0x00]]),
+            Address::PINS_000,
+        );
+        assert_eq!(ds1631.probe(), Ok(true));
+    }
+
+    #[test]
+    fn apply_config_orders_eeprom_writes() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x13], &[0x03], &[0x00]]));
+        let cfg = FullConfig {
+            mode: Mode::OneShot,
+            active_high: true,
+            high: Temperature::from_half_degrees(60),
+            low: Temperature::from_half_degrees(-11),
+        };
+
+        assert!(sensor.apply_config(cfg).is_ok());
+        assert_eq!(sensor.mode, Mode::OneShot);
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_CONFIG, 0x03],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TH, 30, 0x00],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TL, 0xFA, 0x80],
+            ]
+        );
+        assert!(!sensor.needs_settle);
+
+        //En mode continu, la premiere lecture attend une conversion
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x02], &[0x02]]));
+        let continuous = FullConfig {
+            mode: Mode::Continuous,
+            ..cfg
+        };
+        assert!(sensor.apply_config(continuous).is_ok());
+        assert_eq!(sensor.i2c.writes[0], [Register::ACCESS_CONFIG, 0x02]);
+        assert!(sensor.needs_settle);
+
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+        for (high, low, err) in [
+            (50, 50, Error::InvalidParameter),
+            (40, 50, Error::InvalidParameter),
+            (260, 50, Error::OutOfRange),
+            (50, -120, Error::OutOfRange),
+        ] {
+            let cfg = FullConfig {
+                high: Temperature::from_half_degrees(high),
+                low: Temperature::from_half_degrees(low),
+                ..cfg
+            };
+            assert_eq!(sensor.apply_config(cfg), Err(err));
+        }
+        assert!(sensor.i2c.writes.is_empty());
+    }
+
+    #[test]
+    fn reset_config_writes_defaults() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+        sensor.mode = Mode::OneShot;
+        assert!(sensor.reset_config().is_ok());
+        assert_eq!(sensor.mode, Mode::Continuous);
+        assert_eq!(sensor.i2c.writes, [[Register::ACCESS_CONFIG, 0x00]]);
+
+        let mut ds1631 = Ds1621::new_ds1631(I2cMock::default(), Address::PINS_000);
+        assert!(ds1631.reset_config().is_ok());
+        assert_eq!(ds1631.i2c.writes, [[Register::ACCESS_CONFIG, 0x0C]]);
+    }
+
+    #[test]
+    fn config_cache_skips_reads_until_invalidated() {
+        //Configuration initiale, fin des deux ecritures EEPROM, puis capteur
+        //reprogramme par ailleurs (POL a zero, indicateurs leves)
+        let mut sensor =
+            Ds1621::new_default(I2cMock::with_reads(&[&[0x00], &[0x02], &[0x03], &[0x61]]));
+        let config_reads = |writes: &Vec<Vec<u8>>| {
+            writes
+                .iter()
+                .filter(|w| **w == [Register::ACCESS_CONFIG])
+                .count()
+        };
+
+        assert!(sensor.set_polarity(true).is_ok());
+        assert!(sensor.wait_for_nv_write(|| {}).is_ok());
+        assert!(sensor.set_convert_mode(Mode::OneShot).is_ok());
+        assert_eq!(config_reads(&sensor.i2c.writes), 2);
+        //Indicateurs ecrits a 1 depuis le cache : ils restent inchanges
+        assert_eq!(sensor.i2c.writes[3], [Register::ACCESS_CONFIG, 0x63]);
+
+        assert!(sensor.wait_for_nv_write(|| {}).is_ok());
+        sensor.invalidate_cache();
+        assert!(sensor.clear_flags().is_ok());
+        assert_eq!(config_reads(&sensor.i2c.writes), 4);
+        assert_eq!(sensor.i2c.writes[6], [Register::ACCESS_CONFIG, 0x01]);
+    }
+
+    #[test]
+    fn cached_config_keeps_latched_flags() {
+        //THF a zero a la lecture mise en cache, puis leve par le capteur
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x00], &[0x42]]));
+
+        assert_eq!(sensor.read_config(), Ok(0x00));
+        assert!(sensor.set_polarity(true).is_ok());
+        let written = sensor.i2c.writes[1][1];
+        assert_eq!(written & ConfigRegBits::POL, ConfigRegBits::POL);
+        assert_eq!(written & ConfigRegBits::THF, ConfigRegBits::THF);
+
+        //Seul clear_flags les remet a zero (apres la scrutation de NVB)
+        assert!(sensor.clear_flags().is_ok());
+        assert_eq!(sensor.i2c.writes[3], [Register::ACCESS_CONFIG, 0x02]);
+    }
+
+    #[test]
+    fn builder_writes_config_once() {
+        let sensor = Ds1621Builder::new()
+            .address(Address::PINS_001)
+            .mode(Mode::OneShot)
+            .polarity(true)
+            .build(I2cMock::default())
+            .unwrap();
+
+        assert_eq!(sensor.addr, 0x49);
+        assert_eq!(sensor.mode, Mode::OneShot);
+        assert_eq!(sensor.i2c.writes, [[Register::ACCESS_CONFIG, 0x03]]);
+        assert_eq!(
+            Ds1621Builder::new().build(I2cMock::default()).unwrap().addr,
+            0x48
+        );
+    }
+
+    #[test]
+    fn try_new_checks_address() {
+        assert!(Ds1621::try_new(I2cMock::default(), 0x48).is_ok());
+        assert!(Ds1621::try_new(I2cMock::default(), 0x4F).is_ok());
+        assert_eq!(
+            Ds1621::try_new(I2cMock::default(), 0x47).err(),
+            Some(Error::InvalidParameter)
+        );
+        assert_eq!(
+            Ds1621::try_new(I2cMock::default(), 0x50).err(),
+            Some(Error::InvalidParameter)
+        );
+        assert_eq!(Ds1621::new_default(I2cMock::default()).addr, 0x48);
+    }
+
+    #[test]
+    fn from_pins_covers_all_straps() {
+        let table = [
+            (false, false, false, ADDR_PINS_000),
+            (false, false, true, ADDR_PINS_001),
+            (false, true, false, ADDR_PINS_010),
+            (false, true, true, ADDR_PINS_011),
+            (true, false, false, ADDR_PINS_100),
+            (true, false, true, ADDR_PINS_101),
+            (true, true, false, ADDR_PINS_110),
+            (true, true, true, ADDR_PINS_111),
+        ];
+
+        for (i, (a2, a1, a0, addr)) in table.into_iter().enumerate() {
+            assert_eq!(addr, 0x48 + i as u8);
+            assert_eq!(Ds1621::from_pins(I2cMock::default(), a2, a1, a0).addr, addr);
+        }
+    }
+
+    #[test]
+    fn address_only_accepts_legal_values() {
+        assert_eq!(Address::from_u8(0x47), None);
+        assert_eq!(Address::from_u8(0x50), None);
+        assert_eq!(Address::from_u8(0x4B), Some(Address::PINS_011));
+        assert_eq!(Address::from_pins(true, false, true), Address::PINS_101);
+        assert_eq!(u8::from(Address::default()), 0x48);
+        assert_eq!(
+            Ds1621::new(I2cMock::default(), Address::PINS_110).addr,
+            0x4E
+        );
+    }
+
+    #[test]
+    fn polarity_round_trip() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x01], &[0x03], &[0x01]]));
+
+        assert!(sensor.set_polarity(true).is_ok());
+        assert!(sensor.get_polarity().unwrap());
+        assert!(sensor.set_polarity(false).is_ok());
+        assert!(!sensor.get_polarity().unwrap());
+        assert_eq!(sensor.i2c.writes[1], [Register::ACCESS_CONFIG, 0x03]);
+        assert_eq!(sensor.i2c.writes[3], [Register::ACCESS_CONFIG, 0x61]);
+    }
+
+    #[test]
+    fn error_display() {
+        use std::string::ToString;
+
+        assert_eq!(Error::I2C(()).to_string(), "I2C bus error");
+        assert_eq!(
+            Error::<()>::InvalidParameter.to_string(),
+            "invalid parameter"
+        );
+        assert_eq!(Error::<()>::InvalidRegister.to_string(), "invalid register");
+        assert_eq!(Error::<()>::OutOfRange.to_string(), "value out of range");
+        assert_eq!(Error::<()>::Format.to_string(), "formatting error");
+    }
+
+    #[test]
+    fn unified_converts_per_trait_errors() {
+        let mut sensor = Ds1621::new_default(Unified::<_, BusError>::new(SplitErrorBus));
+
+        assert_eq!(sensor.start_convert(), Err(Error::I2C(BusError::Write)));
+        assert_eq!(sensor.read_config(), Err(Error::I2C(BusError::Read)));
+        assert_eq!(
+            Error::I2C(WriteNak).map_i2c(BusError::from),
+            Error::I2C(BusError::Write)
+        );
+        assert_eq!(
+            Error::<WriteNak>::Timeout.map_i2c(BusError::from),
+            Error::Timeout
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_names_still_compile() {
+        let mut sensor: ds1621<I2cMock> = ds1621::new_u8(I2cMock::with_reads(&[&[0x00]]), 0x48);
+        let mode: MODE = MODE::ONE_SHOT;
+
+        assert!(sensor.set_convert_mode(mode).is_ok());
+        assert_eq!(sensor.mode, Mode::OneShot);
+        assert!(matches!(
+            Error::<()>::INVALID_PARAMETER,
+            Error::InvalidParameter
+        ));
+    }
+
+    //Meme bus simule, vu au travers du trait I2c d'embedded-hal 1.0
+    #[cfg(feature = "hal-1")]
+    struct I2cMock1(I2cMock);
+
+    #[cfg(feature = "hal-1")]
+    impl embedded_hal_1::i2c::ErrorType for I2cMock1 {
+        type Error = core::convert::Infallible;
+    }
+
+    #[cfg(feature = "hal-1")]
+    impl embedded_hal_1::i2c::I2c for I2cMock1 {
+        fn transaction(
+            &mut self,
+            _address: u8,
+            operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            use embedded_hal_1::i2c::Operation;
+
+            for op in operations {
+                match op {
+                    Operation::Write(bytes) => self.0.writes.push(bytes.to_vec()),
+                    Operation::Read(buffer) => self.0.next_read(buffer),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(all(feature = "hal-1", feature = "float"))]
+    #[test]
+    fn hal1_adapter() {
+        let mock = I2cMock1(I2cMock::with_reads(&[&[0x19, 0x80]]));
+        let mut sensor = Ds1621::new_default(Hal1::new(mock));
+
+        assert_eq!(sensor.read_temperature().unwrap(), 25.5);
+        assert!(sensor.start_convert().is_ok());
+        assert_eq!(
+            sensor.i2c.into_inner().0.writes,
+            [[Register::TEMPERATURE], [Register::START_CONVERT]]
+        );
+    }
+
+    #[cfg(feature = "hal-1")]
+    #[test]
+    fn two_sensors_on_shared_bus() {
+        use core::cell::RefCell;
+        use embedded_hal_bus::i2c::RefCellDevice;
+
+        let bus = RefCell::new(I2cMock1(I2cMock::with_reads(&[
+            &[0x19, 0x80],
+            &[0xF5, 0x80],
+        ])));
+        let mut inside = Ds1621::new(Hal1::new(RefCellDevice::new(&bus)), Address::PINS_000);
+        let mut outside = Ds1621::new(Hal1::new(RefCellDevice::new(&bus)), Address::PINS_001);
+
+        assert_eq!(inside.read_temperature_millicelsius().unwrap(), 25_500);
+        assert_eq!(outside.read_temperature_millicelsius().unwrap(), -10_500);
+        assert!(inside.start_convert().is_ok());
+        assert!(outside.stop_convert().is_ok());
+        assert_eq!(bus.borrow().0.writes.len(), 4);
+    }
+
+    #[test]
+    fn get_convert_mode_reads_chip() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x8D], &[0x8C]]));
+
+        assert_eq!(sensor.get_convert_mode().unwrap(), Mode::OneShot);
+        assert_eq!(sensor.get_convert_mode().unwrap(), Mode::Continuous);
+    }
+
+    #[test]
+    fn ds1631_command_set() {
+        let mut sensor = Ds1621::new_ds1631(I2cMock::with_reads(&[&[0x0C]]), Address::PINS_001);
+
+        assert_eq!(sensor.variant(), Variant::Ds1631);
+        assert!(sensor.start_convert().is_ok());
+        assert!(sensor.write_config_verified(0x0C).is_ok());
+        assert_eq!(sensor.i2c.writes[0], [Register::START_CONVERT_DS1631]);
+        assert_eq!(
+            Ds1621::new_default(I2cMock::default()).variant(),
+            Variant::Ds1621
+        );
+    }
+
+    #[test]
+    fn set_resolution_per_variant() {
+        let mut ds1621 = Ds1621::new_default(I2cMock::default());
+        assert_eq!(
+            ds1621.set_resolution(Resolution::Bits12),
+            Err(Error::InvalidParameter)
+        );
+        assert!(ds1621.set_resolution(Resolution::Bits9).is_ok());
+        assert!(ds1621.i2c.writes.is_empty());
+
+        let mut ds1631 =
+            Ds1621::new_ds1631(I2cMock::with_reads(&[&[0x8F], &[0x87]]), Address::PINS_000);
+        assert!(ds1631.set_resolution(Resolution::Bits10).is_ok());
+        assert_eq!(ds1631.resolution(), Resolution::Bits10);
+        assert!(ds1631.set_resolution(Resolution::Bits11).is_ok());
+        assert_eq!(ds1631.i2c.writes[1], [Register::ACCESS_CONFIG, 0x87]);
+        assert_eq!(ds1631.i2c.writes[3], [Register::ACCESS_CONFIG, 0x8B]);
+    }
+
+    #[test]
+    fn decode_honours_resolution() {
+        let table = [
+            (Resolution::Bits9, 25_500),
+            (Resolution::Bits10, 25_750),
+            (Resolution::Bits11, 25_875),
+            (Resolution::Bits12, 25_937),
+        ];
+
+        for (resolution, millicelsius) in table {
+            assert_eq!(decode_millicelsius(0x19, 0xF0, resolution), millicelsius);
+        }
+        assert_eq!(decode_millicelsius(0xFF, 0xF0, Resolution::Bits12), -63);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn decode_temp_per_resolution() {
+        //LSB 0xF0 : seuls les bits 7 a (8 - bits de fraction) sont pris en compte
+        let table = [
+            (Resolution::Bits9, 25.5, -0.5),
+            (Resolution::Bits10, 25.75, -0.25),
+            (Resolution::Bits11, 25.875, -0.125),
+            (Resolution::Bits12, 25.9375, -0.0625),
+        ];
+
+        for (resolution, positive, negative) in table {
+            assert_eq!(decode_temp(0x19, 0xF0, resolution), positive);
+            assert_eq!(decode_temp(0xFF, 0xF0, resolution), negative);
+        }
+
+        //DS1631 en 11 bits : la lecture suit la resolution programmee
+        let mut ds1631 = Ds1621::new_ds1631(
+            I2cMock::with_reads(&[&[0x8F], &[0x19, 0xF0]]),
+            Address::PINS_000,
+        );
+        assert!(ds1631.set_resolution(Resolution::Bits11).is_ok());
+        assert_eq!(ds1631.read_temperature(), Ok(25.875));
+        assert_eq!(
+            Ds1621::new_default(I2cMock::default()).resolution(),
+            Resolution::Bits9
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn sample_n_fills_buffer() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00],
+            &[0x19, 0x80],
+            &[0x1A, 0x00],
+        ]));
+        let mut delay = DelayMock::default();
+        let mut out = [0.0; 3];
+
+        //Limite par la taille du tampon
+        assert_eq!(sensor.sample_n(5, 100, &mut delay, &mut out).unwrap(), 3);
+        assert_eq!(out, [25.0, 25.5, 26.0]);
+        assert_eq!(delay.elapsed_ms, 200);
+
+        let mut sensor = Ds1621::new_default(I2cMock::absent());
+        assert_eq!(
+            sensor.sample_n(2, 100, &mut delay, &mut out),
+            Err(Error::I2C(()))
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_temperature_stable_rejects_outlier() {
+        //Un octet parasite (-1°C) au milieu de mesures a 25°C
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00],
+            &[0xFF, 0x00],
+            &[0x19, 0x80],
+        ]));
+        let mut delays = 0;
+
+        assert_eq!(sensor.read_temperature_stable(3, || delays += 1), Ok(25.0));
+        assert_eq!(delays, 2);
+
+        //Nombre pair : moyenne des deux valeurs centrales
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00],
+            &[0x7D, 0x00],
+            &[0x19, 0x80],
+            &[0x1A, 0x00],
+        ]));
+        assert_eq!(sensor.read_temperature_stable(4, || {}), Ok(25.75));
+
+        assert_eq!(
+            sensor.read_temperature_stable(0, || {}),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            sensor.read_temperature_stable(Ds1621::<I2cMock>::MAX_STABLE_SAMPLES + 1, || {}),
+            Err(Error::InvalidParameter)
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_one_shot_blocking_sleeps_once() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x81], &[0x19, 0x80]]));
+        let mut delay = DelayMock::default();
+
+        assert_eq!(sensor.read_one_shot_blocking(&mut delay).unwrap(), 25.5);
+        assert_eq!(delay.elapsed_ms, 750);
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                [Register::START_CONVERT],
+                [Register::ACCESS_CONFIG],
+                [Register::TEMPERATURE]
+            ]
+        );
+
+        //Delai insuffisant : repli sur la scrutation de DONE
+        let mut sensor =
+            Ds1621::new_default(I2cMock::with_reads(&[&[0x01], &[0x81], &[0x19, 0x80]]));
+        let mut delay = DelayMock::default();
+
+        assert_eq!(sensor.read_one_shot_blocking(&mut delay).unwrap(), 25.5);
+        assert_eq!(delay.elapsed_ms, 760);
+        assert_eq!(sensor.i2c.writes.len(), 4);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn read_temperature_with_flags() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x1F, 0x00], &[0b1100_1100]]));
+
+        assert_eq!(
+            sensor.read_temperature_with_flags().unwrap(),
+            (31.0, true, false)
+        );
+        assert_eq!(
+            sensor.i2c.writes,
+            [[Register::TEMPERATURE], [Register::ACCESS_CONFIG]]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn poll_conversion_without_blocking() {
+        let mut sensor =
+            Ds1621::new_default(I2cMock::with_reads(&[&[0x01], &[0x81], &[0x19, 0x80]]));
+
+        assert_eq!(sensor.poll_conversion().unwrap(), Conversion::InProgress);
+        assert_eq!(sensor.poll_conversion().unwrap(), Conversion::Done(25.5));
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                [Register::ACCESS_CONFIG],
+                [Register::ACCESS_CONFIG],
+                [Register::TEMPERATURE]
+            ]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn readings_iterator() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00],
+            &[0x19, 0x80],
+            &[0x1A, 0x00],
+        ]));
+
+        let temps: Vec<f32> = sensor.readings().take(3).map(Result::unwrap).collect();
+        assert_eq!(temps, [25.0, 25.5, 26.0]);
+        assert_eq!(sensor.i2c.writes.len(), 3);
+    }
+
+    #[test]
+    fn release_returns_bus() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+        assert!(sensor.stop_convert().is_ok());
+
+        let i2c = sensor.release();
+        assert_eq!(i2c.writes, [[Register::STOP_CONVERT]]);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn measure_once_restores_continuous_mode() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x00],
+            &[0x01],
+            &[0x81],
+            &[0x19, 0x00],
+        ]));
+        let mut delay = DelayMock::default();
+
+        assert_eq!(sensor.measure_once(&mut delay).unwrap(), 25.0);
+        assert_eq!(delay.elapsed_ms, 10);
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG, 0x61],
+                std::vec![Register::START_CONVERT],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::TEMPERATURE],
+                std::vec![Register::ACCESS_CONFIG, 0xE0],
+            ]
+        );
+    }
+
+    #[test]
+    fn fixed_point_temperature() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x19, 0x80], &[0xF5, 0x80]]));
+
+        assert_eq!(sensor.read_temperature_millicelsius().unwrap(), 25500);
+        assert_eq!(sensor.read_temperature_millicelsius().unwrap(), -10500);
+
+        assert!(sensor
+            .write_threshold_half_degrees(51, Register::ACCESS_TH)
+            .is_ok());
+        assert!(sensor
+            .write_threshold_half_degrees(-11, Register::ACCESS_TL)
+            .is_ok());
+        assert_eq!(
+            sensor.write_threshold_half_degrees(512, Register::ACCESS_TL),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.i2c.writes[2..],
+            [
+                [Register::ACCESS_TH, 0x19, 0x80],
+                [Register::ACCESS_TL, 0xFA, 0x80]
+            ]
+        );
+    }
+
+    #[test]
+    fn write_thresholds_raw_validates_window() {
+        //NVB encore leve a la premiere lecture suivant l'ecriture de TH
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x10], &[0x00]]));
+
+        assert!(sensor.write_thresholds_raw(60, 50).is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_TH, 0x1E, 0x00],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TL, 0x19, 0x00]
+            ]
+        );
+
+        assert_eq!(
+            sensor.write_thresholds_raw(50, 50),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            sensor.write_thresholds_raw(40, 50),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(sensor.write_thresholds_raw(251, 50), Err(Error::OutOfRange));
+        assert_eq!(
+            sensor.write_thresholds_raw(50, -111),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(sensor.i2c.writes.len(), 4);
+    }
+
+    #[test]
+    fn millicelsius_thresholds_snap_to_half_degree() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        assert_eq!(sensor.write_high_temperature_mc(25_300), Ok(25_500));
+        assert_eq!(sensor.write_high_temperature_mc(25_200), Ok(25_000));
+        //Egalite vers le haut, y compris sous zero
+        assert_eq!(sensor.write_low_temperature_mc(-10_250), Ok(-10_000));
+        assert_eq!(sensor.write_low_temperature_mc(-10_300), Ok(-10_500));
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                [Register::ACCESS_TH, 0x19, 0x80],
+                [Register::ACCESS_TH, 0x19, 0x00],
+                [Register::ACCESS_TL, 0xF6, 0x00],
+                [Register::ACCESS_TL, 0xF5, 0x80]
+            ]
+        );
+
+        assert_eq!(sensor.write_high_temperature_mc(125_000), Ok(125_000));
+        assert_eq!(
+            sensor.write_high_temperature_mc(125_250),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.write_low_temperature_mc(i32::MIN),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.write_low_temperature_mc(i32::MAX),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(sensor.i2c.writes.len(), 5);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn millicelsius_and_float_writers_share_range_rule() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        for (mc, temp) in [(125_200, 125.2), (-55_200, -55.2)] {
+            assert_eq!(sensor.write_high_temperature_mc(mc), Err(Error::OutOfRange));
+            assert_eq!(sensor.write_high_temperature(temp), Err(Error::OutOfRange));
+            assert_eq!(
+                sensor.write_threshold_rounded(temp, Register::ACCESS_TH, RoundMode::Nearest),
+                Err(Error::OutOfRange)
+            );
+        }
+        assert!(sensor.i2c.writes.is_empty());
+    }
+
+    #[test]
+    fn raw_threshold_round_trip() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x1E, 0x80], &[0xFA, 0x00]]));
+
+        assert_eq!(sensor.read_th_raw().unwrap(), [0x1E, 0x80]);
+        assert_eq!(sensor.read_tl_raw().unwrap(), [0xFA, 0x00]);
+        assert!(sensor.write_th_raw([0x1E, 0x80]).is_ok());
+        assert!(sensor.write_tl_raw([0xFA, 0x00]).is_ok());
+        assert_eq!(
+            sensor.i2c.writes[2..],
+            [
+                [Register::ACCESS_TH, 0x1E, 0x80],
+                [Register::ACCESS_TL, 0xFA, 0x00]
+            ]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_threshold_waits_for_nv_write() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x10], &[0x10], &[0x00]]));
+        let mut delays = 0;
+
+        assert!(sensor
+            .write_high_temperature_wait(30.0, || delays += 1)
+            .is_ok());
+        assert_eq!(delays, 2);
+        assert_eq!(sensor.i2c.writes[0], [Register::ACCESS_TH, 30, 0x00]);
+        assert_eq!(sensor.i2c.writes.len(), 4);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_threshold_verify_retries_once() {
+        //Premiere relecture fausse de 0.5°C, la reecriture est bonne
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x00],
+            &[0x19, 0x00],
+            &[0x00],
+            &[0x19, 0x80],
+        ]));
+        sensor.set_threshold_verify(true);
+
+        assert_eq!(
+            sensor.write_high_temperature_wait(25.5, || {}).unwrap(),
+            25.5
+        );
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_TH, 0x19, 0x80],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TH],
+                std::vec![Register::ACCESS_TH, 0x19, 0x80],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TH],
+            ]
+        );
+
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x00],
+            &[0x19, 0x00],
+            &[0x00],
+            &[0x19, 0x00],
+        ]));
+        sensor.set_threshold_verify(true);
+
+        assert_eq!(
+            sensor.write_low_temperature_wait(25.5, || {}),
+            Err(Error::Verify)
+        );
+        assert_eq!(sensor.i2c.writes.len(), 6);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_threshold_rejects_out_of_range() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        for temp in [f32::NAN, 130.0, -60.0, f32::INFINITY] {
+            assert_eq!(sensor.write_high_temperature(temp), Err(Error::OutOfRange));
+        }
+        assert!(sensor.i2c.writes.is_empty());
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn trend_tracker_dead_band() {
+        let mut trend = TrendTracker::new(0.5);
+
+        assert_eq!(trend.update(25.0), Trend::Stable);
+        assert_eq!(trend.update(25.5), Trend::Stable);
+        assert_eq!(trend.update(24.5), Trend::Stable);
+        assert_eq!(trend.update(26.0), Trend::Rising);
+        assert_eq!(trend.update(25.5), Trend::Stable);
+        assert_eq!(trend.update(25.0), Trend::Falling);
+
+        //Une derive lente finit par sortir de la bande morte
+        let mut trend = TrendTracker::new(0.5);
+        let steps: Vec<Trend> = [20.0, 20.25, 20.5, 20.75]
+            .iter()
+            .map(|&t| trend.update(t))
+            .collect();
+        assert_eq!(
+            steps,
+            [Trend::Stable, Trend::Stable, Trend::Stable, Trend::Rising]
+        );
+
+        trend.reset();
+        assert_eq!(trend.update(0.0), Trend::Stable);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn smoothed_moving_average() {
+        let sensor = Ds1621::new_default(I2cMock::with_reads(&[
+            &[0x19, 0x00],
+            &[0x19, 0x80],
+            &[0x19, 0x80],
+            &[0x1A, 0x00],
+            &[0x1A, 0x00],
+        ]));
+        let mut smoothed: Smoothed<_, 4> = Smoothed::new(sensor);
+
+        assert_eq!(smoothed.read().unwrap(), 25.0);
+        assert_eq!(smoothed.read().unwrap(), 25.25);
+        assert_eq!(smoothed.read().unwrap(), 25.333334);
+        assert_eq!(smoothed.read().unwrap(), 25.5);
+        //La mesure la plus ancienne (25.0) sort de la fenetre
+        assert_eq!(smoothed.read().unwrap(), 25.75);
+        assert!(smoothed.release().i2c.reads.is_empty());
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_threshold_round_modes() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+        let table = [
+            (25.3, RoundMode::Nearest, 25.5),
+            (25.2, RoundMode::Nearest, 25.0),
+            (25.7, RoundMode::Nearest, 25.5),
+            (25.3, RoundMode::Down, 25.0),
+            (25.7, RoundMode::Down, 25.5),
+            (25.3, RoundMode::Up, 25.5),
+            (25.7, RoundMode::Up, 26.0),
+            (25.5, RoundMode::Up, 25.5),
+            (-10.3, RoundMode::Down, -10.5),
+            (-10.3, RoundMode::Up, -10.0),
+        ];
+
+        for (temp, round, programmed) in table {
+            assert_eq!(
+                sensor
+                    .write_threshold_rounded(temp, Register::ACCESS_TH, round)
+                    .unwrap(),
+                programmed
+            );
+        }
+        assert_eq!(
+            sensor.write_threshold_rounded(125.2, Register::ACCESS_TH, RoundMode::Down),
+            Err(Error::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn temp_limits_match_validation() {
+        type Sensor = Ds1621<I2cMock>;
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+        let max = (Sensor::TEMP_MAX * 2.0) as i16;
+        let min = (Sensor::TEMP_MIN * 2.0) as i16;
+
+        assert!(sensor
+            .write_threshold_half_degrees(max, Register::ACCESS_TH)
+            .is_ok());
+        assert!(sensor
+            .write_threshold_half_degrees(min, Register::ACCESS_TL)
+            .is_ok());
+        assert_eq!(
+            sensor.write_threshold_half_degrees(max + 1, Register::ACCESS_TH),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.write_threshold_half_degrees(min - 1, Register::ACCESS_TL),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.write_thresholds_raw(max + 1, min),
+            Err(Error::OutOfRange)
+        );
+
+        #[cfg(feature = "float")]
+        {
+            assert!(sensor.write_high_temperature(Sensor::TEMP_MAX).is_ok());
+            assert!(sensor.write_low_temperature(Sensor::TEMP_MIN).is_ok());
+            assert_eq!(
+                sensor.write_high_temperature(Sensor::TEMP_MAX + 0.5),
+                Err(Error::OutOfRange)
+            );
+            assert_eq!(
+                sensor.write_low_temperature(Sensor::TEMP_MIN - 0.5),
+                Err(Error::OutOfRange)
+            );
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn threshold_errors_distinguish_register_from_value() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        assert_eq!(
+            sensor.write_threshold_temperature(25.0, Register::ACCESS_CONFIG),
+            Err(Error::InvalidRegister)
+        );
+        assert_eq!(
+            sensor.write_threshold_temperature(130.0, Register::ACCESS_TH),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.write_threshold_half_degrees(50, Register::TEMPERATURE),
+            Err(Error::InvalidRegister)
+        );
+        assert!(sensor.i2c.writes.is_empty());
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn configure_thermostat_window() {
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x00]]));
+
+        assert!(sensor.configure_thermostat(30.0, 25.0).is_ok());
+        for (high, low) in [(25.0, 30.0), (25.0, 25.0), (25.2, 25.0)] {
+            assert_eq!(
+                sensor.configure_thermostat(high, low),
+                Err(Error::InvalidParameter)
+            );
+        }
+        assert_eq!(
+            sensor.configure_thermostat(130.0, 25.0),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_TH, 30, 0x00],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TL, 25, 0x00]
+            ]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn configure_thermostat_waits_for_th_write() {
+        //NVB encore leve a la premiere lecture suivant l'ecriture de TH
+        let mut sensor = Ds1621::new_default(I2cMock::with_reads(&[&[0x10], &[0x00]]));
+
+        assert!(sensor.configure_thermostat(30.0, 25.0).is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                std::vec![Register::ACCESS_TH, 30, 0x00],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_CONFIG],
+                std::vec![Register::ACCESS_TL, 25, 0x00]
+            ]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_threshold_half_degree() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        assert!(sensor.write_high_temperature(25.5).is_ok());
+        assert!(sensor.write_low_temperature(25.2).is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                [Register::ACCESS_TH, 25, 0x80],
+                [Register::ACCESS_TL, 25, 0x00]
+            ]
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_threshold_reports_programmed_value() {
+        let table = [
+            (25.3, 25.5, [25, 0x80]),
+            (25.2, 25.0, [25, 0x00]),
+            (25.25, 25.5, [25, 0x80]),
+            (25.7, 25.5, [25, 0x80]),
+            (25.8, 26.0, [26, 0x00]),
+            (-5.3, -5.5, [0xFA, 0x80]),
+            (-5.2, -5.0, [0xFB, 0x00]),
+            (-0.2, 0.0, [0x00, 0x00]),
+            (125.0, 125.0, [125, 0x00]),
+            (-55.0, -55.0, [0xC9, 0x00]),
+        ];
+
+        for (asked, programmed, bytes) in table {
+            let mut sensor = Ds1621::new_default(I2cMock::default());
+            assert_eq!(sensor.write_high_temperature(asked), Ok(programmed));
+            assert_eq!(
+                sensor.i2c.writes,
+                [[Register::ACCESS_TH, bytes[0], bytes[1]]]
+            );
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn write_negative_threshold() {
+        let mut sensor = Ds1621::new_default(I2cMock::default());
+
+        assert!(sensor.write_high_temperature(-55.0).is_ok());
+        assert!(sensor.write_high_temperature(-0.5).is_ok());
+        assert!(sensor.write_low_temperature(-10.5).is_ok());
+        assert!(sensor.write_low_temperature(-5.5).is_ok());
+        assert_eq!(
+            sensor.i2c.writes,
+            [
+                [Register::ACCESS_TH, 0xC9, 0x00],
+                [Register::ACCESS_TH, 0xFF, 0x80],
+                [Register::ACCESS_TL, 0xF5, 0x80],
+                [Register::ACCESS_TL, 0xFA, 0x80]
+            ]
+        );
+    }
 }
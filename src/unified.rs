@@ -0,0 +1,73 @@
+//! Unification des types d'erreur I2C
+//!
+//! Le driver suppose que `Read`, `Write` et `WriteRead` partagent le meme type
+//! `Error` : c'est le cas de la plupart des HAL, et cela permet a toutes les
+//! methodes de renvoyer un seul [`Error<E>`](crate::Error). Pour une HAL dont chaque
+//! trait a sa propre erreur, [`Unified`] enveloppe le bus et convertit chacune vers
+//! un type commun `E` via `From` :
+//!
+//! ```ignore
+//! enum BusError { Write(WriteError), Read(ReadError) }
+//! impl From<WriteError> for BusError { ... }
+//! impl From<ReadError> for BusError { ... }
+//!
+//! let sensor = Ds1621::new(Unified::<_, BusError>::new(i2c), Address::PINS_000);
+//! ```
+
+use core::marker::PhantomData;
+
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+
+/// Bus I2C dont les erreurs de `Read`, `Write` et `WriteRead` sont converties en `E`
+#[derive(Debug)]
+pub struct Unified<I2C, E> {
+    i2c: I2C,
+    _error: PhantomData<fn() -> E>,
+}
+
+impl<I2C, E> Unified<I2C, E> {
+    pub fn new(i2c: I2C) -> Self {
+        Unified {
+            i2c,
+            _error: PhantomData,
+        }
+    }
+
+    /// Rend le bus I2C d'origine
+    pub fn into_inner(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C: Read, E: From<I2C::Error>> Read for Unified<I2C, E> {
+    type Error = E;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), E> {
+        match self.i2c.read(address, buffer) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(E::from(e)),
+        }
+    }
+}
+
+impl<I2C: Write, E: From<I2C::Error>> Write for Unified<I2C, E> {
+    type Error = E;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), E> {
+        match self.i2c.write(address, bytes) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(E::from(e)),
+        }
+    }
+}
+
+impl<I2C: WriteRead, E: From<I2C::Error>> WriteRead for Unified<I2C, E> {
+    type Error = E;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), E> {
+        match self.i2c.write_read(address, bytes, buffer) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(E::from(e)),
+        }
+    }
+}
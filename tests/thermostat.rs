@@ -0,0 +1,73 @@
+//! Scenario complet du thermostat : programmation de Tout, passage en mode continu,
+//! franchissements des seuils puis remise a zero des indicateurs, transaction par
+//! transaction sur un bus simule.
+
+#![cfg(feature = "float")]
+
+use ds1621::{Ds1621, Status};
+use embedded_hal_mock::eh0::i2c::{Mock, Transaction};
+
+const ADDR: u8 = 0x48;
+
+//Octet de configuration lu pendant le scenario : bits reserves a 1, POL programme
+const CONFIG: u8 = 0b0000_1110;
+const DONE: u8 = 0b1000_0000;
+const THF: u8 = 0b0100_0000;
+const TLF: u8 = 0b0010_0000;
+const NVB: u8 = 0b0001_0000;
+
+#[test]
+fn thermostat_workflow() {
+    let expectations = [
+        //Tout actif a l'etat haut, puis fin de l'ecriture EEPROM
+        Transaction::write_read(ADDR, vec![0xAC], vec![0b0000_1100]),
+        Transaction::write(ADDR, vec![0xAC, CONFIG]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![CONFIG | NVB]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![CONFIG]),
+        //TH = 30°C, TL = 25°C, chacun suivi de l'attente de NVB
+        Transaction::write(ADDR, vec![0xA1, 0x1E, 0x00]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![CONFIG]),
+        Transaction::write(ADDR, vec![0xA2, 0x19, 0x00]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![CONFIG]),
+        //Mode continu (configuration en cache, indicateurs ecrits a 1 pour ne pas
+        //effacer un franchissement survenu depuis) puis Start Convert
+        Transaction::write(ADDR, vec![0xAC, THF | TLF | CONFIG]),
+        Transaction::write(ADDR, vec![0xEE]),
+        //Rechauffement au-dessus de TH : THF se leve
+        Transaction::write_read(ADDR, vec![0xAA], vec![0x1F, 0x00]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![DONE | THF | CONFIG]),
+        //Refroidissement sous TL : TLF se leve, THF reste verrouille
+        Transaction::write_read(ADDR, vec![0xAA], vec![0x18, 0x00]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![DONE | THF | TLF | CONFIG]),
+        //Remise a zero des indicateurs, les autres bits sont reecrits tels quels
+        Transaction::write(ADDR, vec![0xAC, DONE | CONFIG]),
+        Transaction::write_read(ADDR, vec![0xAC], vec![DONE | CONFIG]),
+    ];
+    let mut sensor = Ds1621::new_default(Mock::new(&expectations));
+
+    sensor.set_polarity(true).unwrap();
+    sensor.wait_for_nv_write(|| {}).unwrap();
+    assert_eq!(
+        sensor.write_high_temperature_wait(30.0, || {}).unwrap(),
+        30.0
+    );
+    assert_eq!(
+        sensor.write_low_temperature_wait(25.0, || {}).unwrap(),
+        25.0
+    );
+    sensor.start_continuous().unwrap();
+
+    assert_eq!(sensor.read_temperature().unwrap(), 31.0);
+    let status: Status = sensor.read_status().unwrap();
+    assert!(status.temp_high_flag && !status.temp_low_flag);
+
+    assert_eq!(sensor.read_temperature().unwrap(), 24.0);
+    let status = sensor.read_status().unwrap();
+    assert!(status.temp_high_flag && status.temp_low_flag);
+
+    sensor.clear_flags().unwrap();
+    let status = sensor.read_status().unwrap();
+    assert!(!status.temp_high_flag && !status.temp_low_flag);
+
+    sensor.release().done();
+}
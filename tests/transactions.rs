@@ -0,0 +1,74 @@
+//! Verifie, avec les attentes de transactions d'`embedded-hal-mock`, les octets
+//! exacts envoyes sur le bus par rapport aux commandes de la datasheet.
+
+use ds1621::{Address, Ds1621, Mode};
+use embedded_hal_mock::eh0::i2c::{Mock, Transaction};
+
+const ADDR: u8 = 0x48;
+
+#[test]
+fn start_convert_sends_command() {
+    let mut sensor = Ds1621::new_default(Mock::new(&[Transaction::write(ADDR, vec![0xEE])]));
+
+    sensor.start_convert().unwrap();
+    sensor.release().done();
+}
+
+#[test]
+fn stop_convert_sends_command() {
+    let mut sensor = Ds1621::new(
+        Mock::new(&[Transaction::write(0x4F, vec![0x22])]),
+        Address::PINS_111,
+    );
+
+    sensor.stop_convert().unwrap();
+    sensor.release().done();
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn write_high_temperature_encodes_half_degree() {
+    let mut sensor = Ds1621::new_default(Mock::new(&[Transaction::write(
+        ADDR,
+        vec![0xA1, 0x19, 0x80],
+    )]));
+
+    sensor.write_high_temperature(25.5).unwrap();
+    sensor.release().done();
+}
+
+#[test]
+fn set_convert_mode_read_modify_write() {
+    let mut sensor = Ds1621::new_default(Mock::new(&[
+        Transaction::write_read(ADDR, vec![0xAC], vec![0x8C]),
+        Transaction::write(ADDR, vec![0xAC, 0x8D]),
+    ]));
+
+    sensor.set_convert_mode(Mode::OneShot).unwrap();
+    sensor.release().done();
+}
+
+#[test]
+fn read_config_sends_command_first() {
+    //Une lecture seule serait refusee par le mock : 0xAC doit preceder la lecture
+    let mut sensor = Ds1621::new_default(Mock::new(&[Transaction::write_read(
+        ADDR,
+        vec![0xAC],
+        vec![0x8D],
+    )]));
+
+    assert_eq!(sensor.read_config().unwrap(), 0x8D);
+    sensor.release().done();
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn read_temperature_write_read() {
+    let mut sensor = Ds1621::new(
+        Mock::new(&[Transaction::write_read(0x4A, vec![0xAA], vec![0xF5, 0x80])]),
+        Address::PINS_010,
+    );
+
+    assert_eq!(sensor.read_temperature().unwrap(), -10.5);
+    sensor.release().done();
+}